@@ -0,0 +1,99 @@
+#import bevy_pbr::{
+    forward_io::{FragmentOutput, VertexOutput},
+    mesh_functions::get_tag,
+    pbr_fragment::pbr_input_from_standard_material,
+    pbr_functions::{alpha_discard, apply_pbr_lighting, main_pass_post_lighting_processing},
+}
+#import "shaders/noise/fbm.wgsl"::{domain_warp_3d, fbm_3d};
+#import "shaders/noise/hash.wgsl"::uint_hash_13;
+#import "shaders/noise/worley.wgsl"::worley_3d;
+
+struct AsteroidSurfaceSettings {
+    vein_color: vec4<f32>,
+    vein_emissive: vec4<f32>,
+    vein_amount: f32,
+    vein_metallic: f32,
+    vein_scale: f32,
+    detail_scale: f32,
+    bump_strength: f32,
+    color_variation: f32,
+    roughness_variation: f32,
+}
+
+@group(#{MATERIAL_BIND_GROUP}) @binding(100) var<uniform> settings: AsteroidSurfaceSettings;
+
+const DETAIL_OCTAVES: u32 = 3u;
+// Step in detail space of the finite differences that turn the detail height into a normal.
+const BUMP_STEP: f32 = 0.02;
+const VEIN_WIDTH: f32 = 0.12;
+const VEIN_ROUGHNESS: f32 = 0.35;
+
+// Rock height and its gradient along the two axes of one projection plane. `layer` picks an
+// unrelated slice of the noise for each plane and asteroid.
+fn detail_plane(uv: vec2<f32>, layer: f32) -> vec3<f32> {
+    let h = fbm_3d(vec3<f32>(uv, layer), DETAIL_OCTAVES, 2.0, 0.5);
+    let du = fbm_3d(vec3<f32>(uv + vec2<f32>(BUMP_STEP, 0.0), layer), DETAIL_OCTAVES, 2.0, 0.5);
+    let dv = fbm_3d(vec3<f32>(uv + vec2<f32>(0.0, BUMP_STEP), layer), DETAIL_OCTAVES, 2.0, 0.5);
+    return vec3<f32>(h, vec2<f32>(du - h, dv - h) / BUMP_STEP);
+}
+
+// The detail projected along each world axis, blended by how much the surface faces that axis.
+// Returns the height in x and its world space gradient in yzw.
+fn triplanar_detail(p: vec3<f32>, normal: vec3<f32>, layer: f32) -> vec4<f32> {
+    var weights = pow(abs(normal), vec3<f32>(4.0));
+    weights /= weights.x + weights.y + weights.z;
+    let x = detail_plane(p.yz, layer);
+    let y = detail_plane(p.zx, layer + 17.0);
+    let z = detail_plane(p.xy, layer + 34.0);
+    let height = x.x * weights.x + y.x * weights.y + z.x * weights.z;
+    let gradient = vec3<f32>(0.0, x.y, x.z) * weights.x
+        + vec3<f32>(y.z, 0.0, y.y) * weights.y
+        + vec3<f32>(z.y, z.z, 0.0) * weights.z;
+    return vec4<f32>(height, gradient);
+}
+
+// Thin bands along the edges of warped Worley cells, broken up so they only cover part of the
+// surface. Returns 1 inside a vein.
+fn vein_mask(p: vec3<f32>) -> f32 {
+    let cells = worley_3d(domain_warp_3d(p, 0.4));
+    let width = max(VEIN_WIDTH * settings.vein_amount, 1e-4);
+    let vein = 1.0 - smoothstep(0.0, width, cells.y - cells.x);
+    let coverage = fbm_3d(p * 0.5 + 31.0, 2u, 2.0, 0.5) * 0.5 + settings.vein_amount - 0.5;
+    return vein * smoothstep(0.0, 0.2, coverage);
+}
+
+@fragment
+fn fragment(in: VertexOutput, @builtin(front_facing) is_front: bool) -> FragmentOutput {
+    var pbr_input = pbr_input_from_standard_material(in, is_front);
+    pbr_input.material.base_color = alpha_discard(pbr_input.material, pbr_input.material.base_color);
+
+    let random = uint_hash_13(get_tag(in.instance_index));
+    let position = in.world_position.xyz;
+
+    let detail = triplanar_detail(position * settings.detail_scale, pbr_input.N, random.x * 100.0);
+    let gradient = detail.yzw - dot(detail.yzw, pbr_input.N) * pbr_input.N;
+    pbr_input.N = normalize(pbr_input.N - settings.bump_strength * gradient);
+
+    // Each asteroid gets its own brightness, warmth and roughness. Crevices are darker.
+    let brightness = 1.0 + (random.x - 0.5) * 2.0 * settings.color_variation;
+    let warmth = mix(vec3<f32>(0.9, 0.95, 1.05), vec3<f32>(1.1, 1.0, 0.85), random.y);
+    let cavity = mix(0.6, 1.1, saturate(detail.x * 0.5 + 0.5));
+    let rock_color = pbr_input.material.base_color.rgb * brightness * warmth * cavity;
+    let rock_roughness = saturate(
+        pbr_input.material.perceptual_roughness + (random.z - 0.5) * 2.0 * settings.roughness_variation
+    );
+
+    let vein = vein_mask(position * settings.vein_scale + random * 100.0);
+    pbr_input.material.base_color = vec4<f32>(
+        mix(rock_color, settings.vein_color.rgb, vein),
+        pbr_input.material.base_color.a
+    );
+    pbr_input.material.perceptual_roughness = mix(rock_roughness, VEIN_ROUGHNESS, vein);
+    pbr_input.material.metallic = mix(pbr_input.material.metallic, settings.vein_metallic, vein);
+    pbr_input.material.emissive += settings.vein_emissive * vein;
+
+    var out: FragmentOutput;
+    out.color = apply_pbr_lighting(pbr_input);
+    out.color = main_pass_post_lighting_processing(pbr_input, out.color);
+    return out;
+}
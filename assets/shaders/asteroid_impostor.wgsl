@@ -0,0 +1,105 @@
+#import bevy_pbr::{
+    mesh_functions::{get_tag, get_world_from_local},
+    mesh_view_bindings::view,
+    view_transformations::position_world_to_clip,
+}
+#import "shaders/noise/hash.wgsl"::uint_hash_13;
+#import "shaders/noise/simplex.wgsl"::simplex_3d;
+
+#ifdef PREPASS_PIPELINE
+#import bevy_pbr::prepass_io::FragmentOutput;
+#else
+#import bevy_pbr::mesh_view_bindings::lights;
+#endif
+
+@group(#{MATERIAL_BIND_GROUP}) @binding(0) var<uniform> color: vec4<f32>;
+@group(#{MATERIAL_BIND_GROUP}) @binding(1) var<uniform> emissive: vec4<f32>;
+
+// Half size of the billboard relative to the asteroid's largest scale, enough to fit a stretched
+// rock.
+const IMPOSTOR_SIZE: f32 = 1.4;
+const PI: f32 = 3.14159265;
+
+struct Vertex {
+    @builtin(instance_index) instance_index: u32,
+    @location(0) position: vec3<f32>,
+};
+
+struct ImpostorVertexOutput {
+    @builtin(position) position: vec4<f32>,
+    // Position on the billboard, from -1 to 1 on each axis.
+    @location(0) corner: vec2<f32>,
+    @location(1) @interpolate(flat) right: vec3<f32>,
+    @location(2) @interpolate(flat) up: vec3<f32>,
+    @location(3) @interpolate(flat) forward: vec3<f32>,
+    @location(4) @interpolate(flat) tag: u32,
+};
+
+@vertex
+fn vertex(vertex: Vertex) -> ImpostorVertexOutput {
+    let world_from_local = get_world_from_local(vertex.instance_index);
+    let center = world_from_local[3].xyz;
+    let scale = max(
+        length(world_from_local[0].xyz),
+        max(length(world_from_local[1].xyz), length(world_from_local[2].xyz))
+    );
+    let forward = normalize(view.world_position - center);
+    // Any roll works for a round billboard. The camera's keeps it from spinning as the camera
+    // moves.
+    let right = normalize(cross(view.world_from_view[1].xyz, forward));
+    let up = cross(forward, right);
+
+    let corner = sign(vertex.position.xy);
+    let world_position = center + (right * corner.x + up * corner.y) * scale * IMPOSTOR_SIZE;
+
+    var out: ImpostorVertexOutput;
+    out.position = position_world_to_clip(world_position);
+    out.corner = corner;
+    out.right = right;
+    out.up = up;
+    out.forward = forward;
+    out.tag = get_tag(vertex.instance_index);
+    return out;
+}
+
+// Normal of a sphere bulging out of the billboard, inside a lumpy silhouette. Discards fragments
+// outside the silhouette.
+fn impostor_normal(in: ImpostorVertexOutput) -> vec3<f32> {
+    let seed = uint_hash_13(in.tag) * 100.0;
+    let angle = atan2(in.corner.y, in.corner.x);
+    let edge = 0.65 + 0.12 * simplex_3d(vec3<f32>(cos(angle), sin(angle), 0.0) * 1.5 + seed);
+    let p = in.corner / edge;
+    let d2 = dot(p, p);
+    if d2 > 1.0 {
+        discard;
+    }
+    return normalize(in.right * p.x + in.up * p.y + in.forward * sqrt(1.0 - d2));
+}
+
+#ifdef PREPASS_PIPELINE
+@fragment
+fn fragment(in: ImpostorVertexOutput) -> FragmentOutput {
+    let normal = impostor_normal(in);
+    var out: FragmentOutput;
+#ifdef NORMAL_PREPASS
+    out.normal = vec4<f32>(normal * 0.5 + vec3<f32>(0.5), 1.0);
+#endif
+#ifdef MOTION_VECTOR_PREPASS
+    out.motion_vector = vec2<f32>(0.0);
+#endif
+    return out;
+}
+#else
+@fragment
+fn fragment(in: ImpostorVertexOutput) -> @location(0) vec4<f32> {
+    let normal = impostor_normal(in);
+    let brightness = 0.8 + 0.4 * uint_hash_13(in.tag).x;
+    var light = lights.ambient_color.rgb;
+    if lights.n_directional_lights > 0u {
+        let sun = lights.directional_lights[0];
+        light += sun.color.rgb * max(dot(normal, sun.direction_to_light), 0.0) / PI;
+    }
+    let lit = color.rgb * brightness * light + emissive.rgb;
+    return vec4<f32>(lit * view.exposure, 1.0);
+}
+#endif
@@ -0,0 +1,18 @@
+#import "shaders/noise/simplex.wgsl"::simplex_3d;
+
+fn curl_potential(p: vec3<f32>) -> vec3<f32> {
+    return vec3<f32>(
+        simplex_3d(p),
+        simplex_3d(p + vec3<f32>(31.4, 15.9, 26.5)),
+        simplex_3d(p + vec3<f32>(-35.8, 97.9, -32.3))
+    );
+}
+
+// Divergence free flow field, the curl of a simplex noise vector potential.
+fn curl_3d(p: vec3<f32>) -> vec3<f32> {
+    let e = 0.01;
+    let dx = curl_potential(p + vec3<f32>(e, 0.0, 0.0)) - curl_potential(p - vec3<f32>(e, 0.0, 0.0));
+    let dy = curl_potential(p + vec3<f32>(0.0, e, 0.0)) - curl_potential(p - vec3<f32>(0.0, e, 0.0));
+    let dz = curl_potential(p + vec3<f32>(0.0, 0.0, e)) - curl_potential(p - vec3<f32>(0.0, 0.0, e));
+    return vec3<f32>(dy.z - dz.y, dz.x - dx.z, dx.y - dy.x) / (2.0 * e);
+}
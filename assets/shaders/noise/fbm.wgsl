@@ -0,0 +1,30 @@
+#import "shaders/noise/simplex.wgsl"::simplex_3d;
+
+// Sum of `octaves` layers of simplex noise, normalized back to roughly [-1, 1].
+fn fbm_3d(p: vec3<f32>, octaves: u32, lacunarity: f32, gain: f32) -> f32 {
+    var sum = 0.0;
+    var amplitude = 1.0;
+    var frequency = 1.0;
+    var total = 0.0;
+    for (var i = 0u; i < octaves; i = i + 1u) {
+        sum = sum + amplitude * simplex_3d(p * frequency);
+        total = total + amplitude;
+        amplitude = amplitude * gain;
+        frequency = frequency * lacunarity;
+    }
+    return sum / max(total, 1e-6);
+}
+
+// Moves `p` along three decorrelated fbm fields.
+fn domain_warp_3d(p: vec3<f32>, strength: f32) -> vec3<f32> {
+    let offset = vec3<f32>(
+        fbm_3d(p, 4u, 2.0, 0.5),
+        fbm_3d(p + vec3<f32>(5.2, 1.3, 2.8), 4u, 2.0, 0.5),
+        fbm_3d(p + vec3<f32>(1.7, 9.2, 3.4), 4u, 2.0, 0.5)
+    );
+    return p + strength * offset;
+}
+
+fn warped_fbm_3d(p: vec3<f32>, strength: f32, octaves: u32, lacunarity: f32, gain: f32) -> f32 {
+    return fbm_3d(domain_warp_3d(p, strength), octaves, lacunarity, gain);
+}
@@ -1,26 +1,40 @@
-const SINE_FACTOR: f32 = 43758.5453123;
+// PCG3D from "Hash Functions for GPU Rendering" (Jarzynski, Olano). Integer only, so the Rust
+// port in `src/noise/hash.rs` matches it exactly.
+fn pcg_3d(v_in: vec3<u32>) -> vec3<u32> {
+    var v = v_in * 1664525u + 1013904223u;
+    v.x += v.y * v.z;
+    v.y += v.z * v.x;
+    v.z += v.x * v.y;
+    v ^= v >> vec3<u32>(16u);
+    v.x += v.y * v.z;
+    v.y += v.z * v.x;
+    v.z += v.x * v.y;
+    return v;
+}
 
-fn hash_21(p: vec2<f32>) -> f32 {
-    let d = dot(p, vec2<f32>(12.9898, 78.233));
-    let s = sin(d);
-    let f = fract(s * SINE_FACTOR);
-    return f;
+// Three values in [0, 1) for an integer lattice cell.
+fn cell_hash_33(cell: vec3<f32>) -> vec3<f32> {
+    let h = pcg_3d(bitcast<vec3<u32>>(vec3<i32>(cell)));
+    return vec3<f32>(h >> vec3<u32>(8u)) * (1.0 / 16777216.0);
 }
 
-fn hash_31(p: vec3<f32>) -> f32 {
-    let d = dot(p, vec3<f32>(127.1, 311.7, 74.7));
-    let s = sin(d);
-    let f = fract(s * SINE_FACTOR);
-    return f;
+// Three values in [0, 1) for an integer id, such as a `MeshTag`.
+fn uint_hash_13(x: u32) -> vec3<f32> {
+    let h = pcg_3d(vec3<u32>(x, x ^ 0x5bd1e995u, 0x27d4eb2fu));
+    return vec3<f32>(h >> vec3<u32>(8u)) * (1.0 / 16777216.0);
 }
 
+// Three values in [0, 1) for a point. The point's exact bits are hashed, so unlike `sin` based
+// hashes the result is the same on every GPU and in the Rust port.
 fn hash_33(p: vec3<f32>) -> vec3<f32> {
-    let d = vec3<f32>(
-        dot(p, vec3<f32>(127.1, 311.7, 74.7)),
-        dot(p, vec3<f32>(269.5, 183.3, 246.1)),
-        dot(p, vec3<f32>(113.5, 271.9, 124.6))
-    );
-    let s = sin(d);
-    let f = fract(s * SINE_FACTOR);
-    return f;
+    let h = pcg_3d(bitcast<vec3<u32>>(p));
+    return vec3<f32>(h >> vec3<u32>(8u)) * (1.0 / 16777216.0);
+}
+
+fn hash_31(p: vec3<f32>) -> f32 {
+    return hash_33(p).x;
+}
+
+fn hash_21(p: vec2<f32>) -> f32 {
+    return hash_33(vec3<f32>(p, 0.0)).x;
 }
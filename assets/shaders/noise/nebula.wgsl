@@ -0,0 +1,14 @@
+#import "shaders/noise/value.wgsl"::value_3d;
+#import "shaders/noise/hash.wgsl"::cell_hash_33;
+
+// Cells per unit of the grain layer.
+const NEBULA_GRAIN_FREQUENCY: f32 = 16.0;
+
+// Noise vector of the volumetric nebula, whose length is compared against the iso value. Two value
+// noise layers give the shape, and a fine integer hashed layer adds grain.
+fn nebula_noise(point: vec3<f32>) -> vec4<f32> {
+    let n1 = value_3d(point * 0.2 * vec3<f32>(0.75, 1.25, 0.75)) * vec4<f32>(0.8, 0.2, 1.0, 1.0);
+    let n2 = value_3d(point * 0.7 * vec3<f32>(1.25, 0.75, 1.25)) * vec4<f32>(0.1, 1.0, 0.2, 1.0);
+    let n4 = cell_hash_33(floor(point * NEBULA_GRAIN_FREQUENCY)).x * vec4<f32>(1.0, 1.0, 0.8, 1.0);
+    return (n1 * 28.0 + n2 * 18.0 + n4 * 4.0) / 50.0;
+}
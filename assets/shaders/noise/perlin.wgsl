@@ -0,0 +1,29 @@
+#import "shaders/noise/hash.wgsl"::cell_hash_33;
+
+fn perlin_gradient(cell: vec3<f32>, offset: vec3<f32>) -> f32 {
+    return dot(cell_hash_33(cell) * 2.0 - 1.0, offset);
+}
+
+// 3D gradient noise with quintic fade, roughly in [-1, 1].
+fn perlin_3d(p: vec3<f32>) -> f32 {
+    let i = floor(p);
+    let f = p - i;
+    let u = f * f * f * (f * (f * 6.0 - 15.0) + 10.0);
+
+    let n000 = perlin_gradient(i, f);
+    let n100 = perlin_gradient(i + vec3<f32>(1.0, 0.0, 0.0), f - vec3<f32>(1.0, 0.0, 0.0));
+    let n010 = perlin_gradient(i + vec3<f32>(0.0, 1.0, 0.0), f - vec3<f32>(0.0, 1.0, 0.0));
+    let n110 = perlin_gradient(i + vec3<f32>(1.0, 1.0, 0.0), f - vec3<f32>(1.0, 1.0, 0.0));
+    let n001 = perlin_gradient(i + vec3<f32>(0.0, 0.0, 1.0), f - vec3<f32>(0.0, 0.0, 1.0));
+    let n101 = perlin_gradient(i + vec3<f32>(1.0, 0.0, 1.0), f - vec3<f32>(1.0, 0.0, 1.0));
+    let n011 = perlin_gradient(i + vec3<f32>(0.0, 1.0, 1.0), f - vec3<f32>(0.0, 1.0, 1.0));
+    let n111 = perlin_gradient(i + vec3<f32>(1.0, 1.0, 1.0), f - vec3<f32>(1.0, 1.0, 1.0));
+
+    let x00 = mix(n000, n100, u.x);
+    let x10 = mix(n010, n110, u.x);
+    let x01 = mix(n001, n101, u.x);
+    let x11 = mix(n011, n111, u.x);
+    let y0 = mix(x00, x10, u.y);
+    let y1 = mix(x01, x11, u.y);
+    return mix(y0, y1, u.z);
+}
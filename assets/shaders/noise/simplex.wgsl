@@ -0,0 +1,31 @@
+#import "shaders/noise/hash.wgsl"::cell_hash_33;
+
+fn simplex_corner(cell: vec3<f32>, offset: vec3<f32>) -> f32 {
+    let t = max(0.6 - dot(offset, offset), 0.0);
+    let t2 = t * t;
+    return t2 * t2 * dot(cell_hash_33(cell) * 2.0 - 1.0, offset);
+}
+
+// 3D simplex noise (Gustavson), roughly in [-1, 1].
+fn simplex_3d(p: vec3<f32>) -> f32 {
+    let skew = 1.0 / 3.0;
+    let unskew = 1.0 / 6.0;
+
+    let i = floor(p + (p.x + p.y + p.z) * skew);
+    let x0 = p - i + (i.x + i.y + i.z) * unskew;
+
+    // Order the corners by which axes the point is furthest along.
+    let g = step(x0.yzx, x0.xyz);
+    let l = 1.0 - g;
+    let i1 = min(g, l.zxy);
+    let i2 = max(g, l.zxy);
+
+    let x1 = x0 - i1 + unskew;
+    let x2 = x0 - i2 + 2.0 * unskew;
+    let x3 = x0 - 1.0 + 3.0 * unskew;
+
+    return 32.0 * (simplex_corner(i, x0)
+        + simplex_corner(i + i1, x1)
+        + simplex_corner(i + i2, x2)
+        + simplex_corner(i + 1.0, x3));
+}
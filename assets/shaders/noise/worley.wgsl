@@ -0,0 +1,26 @@
+#import "shaders/noise/hash.wgsl"::cell_hash_33;
+
+// Distances to the nearest (F1) and second nearest (F2) feature points, one point per cell.
+fn worley_3d(p: vec3<f32>) -> vec2<f32> {
+    let cell = floor(p);
+    let local = p - cell;
+
+    var f1 = 8.0;
+    var f2 = 8.0;
+    for (var z: i32 = -1; z <= 1; z = z + 1) {
+        for (var y: i32 = -1; y <= 1; y = y + 1) {
+            for (var x: i32 = -1; x <= 1; x = x + 1) {
+                let offset = vec3<f32>(f32(x), f32(y), f32(z));
+                let r = offset + cell_hash_33(cell + offset) - local;
+                let d = dot(r, r);
+                if d < f1 {
+                    f2 = f1;
+                    f1 = d;
+                } else if d < f2 {
+                    f2 = d;
+                }
+            }
+        }
+    }
+    return sqrt(vec2<f32>(f1, f2));
+}
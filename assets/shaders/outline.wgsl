@@ -1,36 +1,59 @@
 #import bevy_core_pipeline::fullscreen_vertex_shader::FullscreenVertexOutput;
-#import "shaders/noise/value.wgsl"::value_3d;
-#import "shaders/noise/hash.wgsl"::hash_31;
 
 @group(0) @binding(0) var main_texture: texture_2d<f32>;
 @group(0) @binding(1) var main_texture_sampler: sampler;
 
-@group(0) @binding(2) var depth_texture: texture_depth_multisampled_2d; // 4 samples
-@group(0) @binding(3) var normals_texture: texture_multisampled_2d<f32>; // 4 samples
-
 struct OutlineSettings {
+  color: vec4<f32>,
+  thickness: f32,
+  depth_threshold: f32,
+  normal_threshold: f32,
   cutoff: f32,
 }
-@group(0) @binding(4) var<uniform> settings: OutlineSettings;
+@group(0) @binding(2) var<uniform> settings: OutlineSettings;
+
+@group(0) @binding(3) var depth_texture: texture_depth_multisampled_2d; // 4 samples
+@group(0) @binding(4) var normals_texture: texture_multisampled_2d<f32>; // 4 samples
+@group(0) @binding(5) var mask_texture: texture_2d<f32>;
+
+// Pixel offsets of the four diagonal Roberts cross samples, scaled by the outline thickness.
+fn roberts_cross_offsets() -> array<vec2<i32>, 4> {
+    let radius = max(i32(round(settings.thickness * 0.5)), 1);
+    return array<vec2<i32>, 4>(
+        vec2<i32>(-radius, radius),
+        vec2<i32>(radius, radius),
+        vec2<i32>(-radius, -radius),
+        vec2<i32>(radius, -radius),
+    );
+}
+
+fn clamp_to_texture(pixel_position: vec2<i32>, dimensions: vec2<u32>) -> vec2<i32> {
+    return clamp(pixel_position, vec2<i32>(0), vec2<i32>(dimensions) - 1);
+}
 
+// Depth difference relative to the center depth, so distant edges are found as easily as near ones.
 fn roberts_cross_depth(pixel_position: vec2<i32>) -> f32 {
-    let tl = textureLoad(depth_texture, pixel_position + vec2<i32>(-1, 1), 0);
-    let tr = textureLoad(depth_texture, pixel_position + vec2<i32>(1, 1), 0);
-    let bl = textureLoad(depth_texture, pixel_position + vec2<i32>(-1, -1), 0);
-    let br = textureLoad(depth_texture, pixel_position + vec2<i32>(1, -1), 0);
+    let offsets = roberts_cross_offsets();
+    let dimensions = textureDimensions(depth_texture);
+    let tl = textureLoad(depth_texture, clamp_to_texture(pixel_position + offsets[0], dimensions), 0);
+    let tr = textureLoad(depth_texture, clamp_to_texture(pixel_position + offsets[1], dimensions), 0);
+    let bl = textureLoad(depth_texture, clamp_to_texture(pixel_position + offsets[2], dimensions), 0);
+    let br = textureLoad(depth_texture, clamp_to_texture(pixel_position + offsets[3], dimensions), 0);
+    let center = textureLoad(depth_texture, pixel_position, 0);
 
     let gx = tl - br;
     let gy = tr - bl;
 
-    let value = sqrt(pow(gx, 2.0) + pow(gy, 2.0));
-    return select(0.0, 1.0, value > 0.0001);
+    return sqrt(gx * gx + gy * gy) / max(center, 1e-6);
 }
 
 fn roberts_cross_normals(pixel_position: vec2<i32>) -> f32 {
-    let tl = textureLoad(normals_texture, pixel_position + vec2<i32>(-1, 1), 0).rgb;
-    let tr = textureLoad(normals_texture, pixel_position + vec2<i32>(1, 1), 0).rgb;
-    let bl = textureLoad(normals_texture, pixel_position + vec2<i32>(-1, -1), 0).rgb;
-    let br = textureLoad(normals_texture, pixel_position + vec2<i32>(1, -1), 0).rgb;
+    let offsets = roberts_cross_offsets();
+    let dimensions = textureDimensions(normals_texture);
+    let tl = textureLoad(normals_texture, clamp_to_texture(pixel_position + offsets[0], dimensions), 0).rgb;
+    let tr = textureLoad(normals_texture, clamp_to_texture(pixel_position + offsets[1], dimensions), 0).rgb;
+    let bl = textureLoad(normals_texture, clamp_to_texture(pixel_position + offsets[2], dimensions), 0).rgb;
+    let br = textureLoad(normals_texture, clamp_to_texture(pixel_position + offsets[3], dimensions), 0).rgb;
 
     let gx = tl - br;
     let gy = tr - bl;
@@ -38,12 +61,32 @@ fn roberts_cross_normals(pixel_position: vec2<i32>) -> f32 {
     return sqrt(dot(gx, gx) + dot(gy, gy));
 }
 
+// Returns the silhouette edge in x and whether any sample lies on an outlined entity in y.
+fn roberts_cross_mask(pixel_position: vec2<i32>) -> vec2<f32> {
+    let offsets = roberts_cross_offsets();
+    let dimensions = textureDimensions(mask_texture);
+    let tl = step(settings.cutoff, textureLoad(mask_texture, clamp_to_texture(pixel_position + offsets[0], dimensions), 0).a);
+    let tr = step(settings.cutoff, textureLoad(mask_texture, clamp_to_texture(pixel_position + offsets[1], dimensions), 0).a);
+    let bl = step(settings.cutoff, textureLoad(mask_texture, clamp_to_texture(pixel_position + offsets[2], dimensions), 0).a);
+    let br = step(settings.cutoff, textureLoad(mask_texture, clamp_to_texture(pixel_position + offsets[3], dimensions), 0).a);
+
+    let gx = tl - br;
+    let gy = tr - bl;
+
+    return vec2<f32>(select(0.0, 1.0, gx != 0.0 || gy != 0.0), max(max(tl, tr), max(bl, br)));
+}
+
 @fragment
 fn fragment(in: FullscreenVertexOutput) -> @location(0) vec4<f32> {
     let pixel_position = vec2<i32>(in.position.xy);
     let main_color = textureSample(main_texture, main_texture_sampler, in.uv);
-    let normals_color = roberts_cross_normals(pixel_position);
-    let depth_color = roberts_cross_depth(pixel_position);
-    let edge_color = max(0.0, depth_color);
-    return main_color + vec4<f32>(edge_color);
+    let mask = roberts_cross_mask(pixel_position);
+    if mask.y == 0.0 {
+        return main_color;
+    }
+    // Creases and overlapping parts inside outlined entities are only drawn where the mask covers them.
+    let depth_edge = step(settings.depth_threshold, roberts_cross_depth(pixel_position));
+    let normal_edge = step(settings.normal_threshold, roberts_cross_normals(pixel_position));
+    let edge = max(mask.x, max(depth_edge, normal_edge));
+    return vec4<f32>(mix(main_color.rgb, settings.color.rgb, edge * settings.color.a), main_color.a);
 }
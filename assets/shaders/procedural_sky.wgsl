@@ -0,0 +1,145 @@
+#import "shaders/noise/hash.wgsl"::{hash_31, hash_33};
+#import "shaders/noise/value.wgsl"::value_3d;
+
+const MAX_SKYBOX_BODIES: u32 = 4u;
+
+struct SkyboxBody {
+  direction: vec3<f32>,
+  angular_radius: f32,
+  color: vec3<f32>,
+  emission: f32,
+}
+
+struct SkyboxParameters {
+  seed_offset: vec3<f32>,
+  background_low: vec3<f32>,
+  background_high: vec3<f32>,
+  star_scale: f32,
+  star_density: f32,
+  star_size: f32,
+  star_brightness: f32,
+  star_brightness_variation: f32,
+  star_temperature_range: vec2<f32>,
+  galaxy_normal: vec3<f32>,
+  galaxy_color: vec3<f32>,
+  galaxy_width: f32,
+  galaxy_intensity: f32,
+  wisp_color_a: vec3<f32>,
+  wisp_color_b: vec3<f32>,
+  wisp_scale: f32,
+  wisp_intensity: f32,
+  light_direction: vec3<f32>,
+  body_count: u32,
+  bodies: array<SkyboxBody, MAX_SKYBOX_BODIES>,
+}
+
+// Approximate blackbody color (Tanner Helland's fit) converted to linear color.
+fn blackbody(temperature: f32) -> vec3<f32> {
+    let t = temperature / 100.0;
+    var color: vec3<f32>;
+    if t <= 66.0 {
+        color.r = 1.0;
+        color.g = 0.39008157 * log(t) - 0.63184144;
+        color.b = select(0.54320678 * log(t - 10.0) - 1.19625408, 0.0, t <= 19.0);
+    } else {
+        color.r = 1.29293618 * pow(t - 60.0, -0.1332047592);
+        color.g = 1.12989086 * pow(t - 60.0, -0.0755148492);
+        color.b = 1.0;
+    }
+    return pow(saturate(color), vec3<f32>(2.2));
+}
+
+// Stars at jittered cell centers, each with its own temperature and brightness.
+fn stars(direction: vec3<f32>, scale: f32, density: f32, parameters: SkyboxParameters) -> vec3<f32> {
+    let p = direction * scale + parameters.seed_offset;
+    let p_floor = floor(p);
+    let p_fract = fract(p);
+
+    var nearest_distance = 100.0;
+    var nearest_cell = vec3<f32>(0.0);
+    for (var x: f32 = -1.0; x <= 1.0; x = x + 1.0) {
+        for (var y: f32 = -1.0; y <= 1.0; y = y + 1.0) {
+            for (var z: f32 = -1.0; z <= 1.0; z = z + 1.0) {
+                let cell = p_floor + vec3<f32>(x, y, z);
+                let r = vec3<f32>(x, y, z) - p_fract + hash_33(cell);
+                let d = dot(r, r);
+                if d < nearest_distance {
+                    nearest_distance = d;
+                    nearest_cell = cell;
+                }
+            }
+        }
+    }
+
+    let random = hash_33(nearest_cell + vec3<f32>(17.0, 59.0, 113.0));
+    if random.x > density {
+        return vec3<f32>(0.0);
+    }
+    let shape = 1.0 - smoothstep(parameters.star_size * 0.25, parameters.star_size, nearest_distance);
+    let brightness = parameters.star_brightness
+        * mix(1.0, random.y * random.y * random.y, parameters.star_brightness_variation);
+    let temperature = mix(parameters.star_temperature_range.x, parameters.star_temperature_range.y, random.z);
+    return blackbody(temperature) * brightness * shape;
+}
+
+fn galaxy_band(direction: vec3<f32>, parameters: SkyboxParameters) -> f32 {
+    let height = dot(direction, parameters.galaxy_normal) / max(parameters.galaxy_width, 1e-4);
+    return exp(-height * height);
+}
+
+fn wisps(direction: vec3<f32>, parameters: SkyboxParameters) -> vec3<f32> {
+    let p = direction * parameters.wisp_scale + parameters.seed_offset;
+    let shape = value_3d(p) * value_3d(p * 2.3 + 11.0) + value_3d(p * 5.1 + 37.0) * 0.15;
+    let strength = smoothstep(0.25, 0.7, shape);
+    let color = mix(parameters.wisp_color_a, parameters.wisp_color_b, value_3d(p * 0.5 + 71.0));
+    return color * strength * parameters.wisp_intensity;
+}
+
+// Color and coverage of a distant planet or sun, with a soft glow around suns.
+fn body(direction: vec3<f32>, sky_body: SkyboxBody, light_direction: vec3<f32>) -> vec4<f32> {
+    let cos_angle = dot(direction, sky_body.direction);
+    let angle = acos(clamp(cos_angle, -1.0, 1.0));
+    let relative = angle / sky_body.angular_radius;
+    if sky_body.emission > 0.0 {
+        let disc = 1.0 - smoothstep(0.9, 1.0, relative);
+        let glow = exp(-relative * 0.5) * 0.1 * step(0.0, cos_angle);
+        return vec4<f32>(sky_body.color * sky_body.emission * (disc + glow), disc);
+    }
+    if relative >= 1.0 {
+        return vec4<f32>(0.0);
+    }
+    // Reconstruct the sphere normal from the offset of the direction from the planet center.
+    let offset = (direction - sky_body.direction * cos_angle) / sin(sky_body.angular_radius);
+    let normal = normalize(offset - sky_body.direction * sqrt(max(1.0 - dot(offset, offset), 0.0)));
+    let lit = saturate(dot(normal, -light_direction));
+    let rim = pow(1.0 - saturate(-dot(normal, sky_body.direction)), 4.0);
+    let coverage = 1.0 - smoothstep(0.95, 1.0, relative);
+    let color = sky_body.color * (lit + 0.02) + sky_body.color * rim * lit * 0.5;
+    return vec4<f32>(color, coverage);
+}
+
+// Sky color seen along a normalized world space direction.
+fn sky_color(direction: vec3<f32>, parameters: SkyboxParameters) -> vec3<f32> {
+    var color = mix(
+        parameters.background_low,
+        parameters.background_high,
+        value_3d(direction * 3.0 + parameters.seed_offset)
+    );
+    let dither_strength = 0.002;
+    color = color + (dither_strength * hash_31(direction * 1000.0)) - (dither_strength * 0.5);
+
+    let band = galaxy_band(direction, parameters);
+    let band_detail = value_3d(direction * 8.0 + parameters.seed_offset) * 0.6 + 0.4;
+    color = color + parameters.galaxy_color * band * band_detail * parameters.galaxy_intensity;
+    color = color + wisps(direction, parameters);
+    color = color + stars(direction, parameters.star_scale, parameters.star_density, parameters);
+    // Dense faint stars that only show up in the galactic band.
+    color = color + stars(direction, parameters.star_scale * 2.5, band * band_detail, parameters) * 0.5;
+
+    for (var i: u32 = 0u; i < min(parameters.body_count, MAX_SKYBOX_BODIES); i = i + 1u) {
+        let sky_body = body(direction, parameters.bodies[i], parameters.light_direction);
+        color = mix(color, sky_body.rgb, sky_body.a) + sky_body.rgb * (1.0 - sky_body.a);
+    }
+
+    return color;
+}
@@ -0,0 +1,29 @@
+#import bevy_core_pipeline::fullscreen_vertex_shader::FullscreenVertexOutput;
+#import "shaders/procedural_sky.wgsl"::{SkyboxParameters, sky_color};
+
+struct SkyboxBake {
+  parameters: SkyboxParameters,
+  face: u32,
+}
+@group(0) @binding(0) var<uniform> bake: SkyboxBake;
+
+// Direction through a texel of a cube face, in the face order +X, -X, +Y, -Y, +Z, -Z.
+fn cube_direction(face: u32, uv: vec2<f32>) -> vec3<f32> {
+    let u = uv.x * 2.0 - 1.0;
+    let v = uv.y * 2.0 - 1.0;
+    switch face {
+        case 0u: { return vec3<f32>(1.0, -v, -u); }
+        case 1u: { return vec3<f32>(-1.0, -v, u); }
+        case 2u: { return vec3<f32>(u, 1.0, v); }
+        case 3u: { return vec3<f32>(u, -1.0, -v); }
+        case 4u: { return vec3<f32>(u, -v, 1.0); }
+        default: { return vec3<f32>(-u, -v, -1.0); }
+    }
+}
+
+@fragment
+fn fragment(in: FullscreenVertexOutput) -> @location(0) vec4<f32> {
+    // Cube maps are left-handed, so Bevy samples them with z negated.
+    let direction = normalize(cube_direction(bake.face, in.uv) * vec3<f32>(1.0, 1.0, -1.0));
+    return vec4<f32>(sky_color(direction, bake.parameters), 1.0);
+}
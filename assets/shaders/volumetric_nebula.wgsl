@@ -1,10 +1,11 @@
 #import bevy_core_pipeline::fullscreen_vertex_shader::FullscreenVertexOutput;
-#import "shaders/noise/value.wgsl"::value_3d;
-#import "shaders/noise/hash.wgsl"::hash_31;
+#import "shaders/noise/nebula.wgsl"::nebula_noise;
 
 @group(0) @binding(0) var main_texture: texture_2d<f32>;
 @group(0) @binding(1) var main_texture_sampler: sampler;
 
+const PI: f32 = 3.14159265358979;
+
 struct VolumetricNebulaSettings {
   time: f32,
   camera_position: vec3<f32>,
@@ -12,53 +13,324 @@ struct VolumetricNebulaSettings {
   camera_up: vec3<f32>,
   camera_forward: vec3<f32>,
   light_direction: vec3<f32>,
-  speed: f32,
-  scale: f32,
   iso_value: f32,
   step_count: i32,
   step_distance: f32,
+  near: f32,
+  tan_half_fov: f32,
+  aspect_ratio: f32,
+  light_color: vec3<f32>,
+  light_intensity: f32,
+  ambient_color: vec3<f32>,
+  density: f32,
+  absorption: f32,
+  scattering: f32,
+  anisotropy: f32,
+  shadow_step_count: i32,
+  shadow_step_distance: f32,
+  max_distance: f32,
+  resolution_scale: f32,
+  history_blend: f32,
+  frame_index: u32,
+  previous_camera_position: vec3<f32>,
+  previous_camera_right: vec3<f32>,
+  previous_camera_up: vec3<f32>,
+  previous_camera_forward: vec3<f32>,
+}
+
+struct NebulaGradient {
+  colors: array<vec4<f32>, 4>,
+  stops: vec4<f32>,
+}
+
+const SHAPE_SPHERE: u32 = 0u;
+
+struct NebulaVolume {
+  local_from_world: mat4x4<f32>,
+  extents: vec3<f32>,
+  shape: u32,
+  density: f32,
+  noise_scale: f32,
+  animation_speed: f32,
+  edge_falloff: f32,
+  gradient: NebulaGradient,
+}
+
+struct NebulaVolumes {
+  count: u32,
+  volumes: array<NebulaVolume>,
 }
 @group(0) @binding(2) var<uniform> settings: VolumetricNebulaSettings;
+@group(0) @binding(3) var depth_texture: texture_depth_multisampled_2d;
+@group(0) @binding(4) var march_texture: texture_2d<f32>;
+@group(0) @binding(5) var history_texture: texture_2d<f32>;
+@group(0) @binding(6) var nebula_sampler: sampler;
+@group(0) @binding(7) var nebula_texture: texture_2d<f32>;
+@group(0) @binding(8) var<storage, read> nebula_volumes: NebulaVolumes;
+
+// Distance from the volume center relative to its bounds, 1 on the boundary.
+fn volume_edge(volume: NebulaVolume, local: vec3<f32>) -> f32 {
+    if volume.shape == SHAPE_SPHERE {
+        return length(local) / volume.extents.x;
+    }
+    let relative = abs(local) / volume.extents;
+    return max(relative.x, max(relative.y, relative.z));
+}
+
+// Density above the iso surface inside the volume, fading out towards its bounds.
+fn volume_density(volume: NebulaVolume, p: vec3<f32>) -> f32 {
+    let local = (volume.local_from_world * vec4<f32>(p, 1.0)).xyz;
+    let edge = volume_edge(volume, local);
+    if edge >= 1.0 {
+        return 0.0;
+    }
+    let mask = 1.0 - smoothstep(1.0 - volume.edge_falloff, 1.0, edge);
+    let point = p * volume.noise_scale + vec3<f32>(settings.time * volume.animation_speed);
+    return max(length(nebula_noise(point)) - settings.iso_value, 0.0) * volume.density * mask;
+}
+
+fn density_at(p: vec3<f32>) -> f32 {
+    var density = 0.0;
+    for (var i: u32 = 0u; i < nebula_volumes.count; i = i + 1u) {
+        density = density + volume_density(nebula_volumes.volumes[i], p);
+    }
+    return density * settings.density;
+}
+
+fn gradient_color(gradient: NebulaGradient, t: f32) -> vec3<f32> {
+    var color = gradient.colors[0].rgb;
+    for (var i: i32 = 1; i < 4; i = i + 1) {
+        let blend = smoothstep(gradient.stops[i - 1], gradient.stops[i], t);
+        color = mix(color, gradient.colors[i].rgb, blend);
+    }
+    return color;
+}
+
+// Total density in a, and the volumes' gradient colors weighted by their density in rgb.
+fn sample_nebula(p: vec3<f32>) -> vec4<f32> {
+    var sample = vec4<f32>(0.0);
+    for (var i: u32 = 0u; i < nebula_volumes.count; i = i + 1u) {
+        let volume = nebula_volumes.volumes[i];
+        let density = volume_density(volume, p);
+        if density > 0.0 {
+            let albedo = gradient_color(volume.gradient, saturate(density / volume.density));
+            sample = sample + vec4<f32>(albedo * density, density);
+        }
+    }
+    return sample * settings.density;
+}
+
+// Entry and exit distances of a ray through the volume bounds, entry > exit when it misses.
+fn volume_interval(volume: NebulaVolume, origin: vec3<f32>, direction: vec3<f32>) -> vec2<f32> {
+    let local_origin = (volume.local_from_world * vec4<f32>(origin, 1.0)).xyz;
+    let local_direction = (volume.local_from_world * vec4<f32>(direction, 0.0)).xyz;
+    if volume.shape == SHAPE_SPHERE {
+        let a = dot(local_direction, local_direction);
+        let b = dot(local_origin, local_direction);
+        let c = dot(local_origin, local_origin) - volume.extents.x * volume.extents.x;
+        let discriminant = b * b - a * c;
+        if discriminant < 0.0 {
+            return vec2<f32>(1.0, 0.0);
+        }
+        let root = sqrt(discriminant);
+        return vec2<f32>(-b - root, -b + root) / a;
+    }
+    let safe_direction = select(local_direction, vec3<f32>(1e-8), abs(local_direction) < vec3<f32>(1e-8));
+    let t0 = (-volume.extents - local_origin) / safe_direction;
+    let t1 = (volume.extents - local_origin) / safe_direction;
+    let near = min(t0, t1);
+    let far = max(t0, t1);
+    return vec2<f32>(max(near.x, max(near.y, near.z)), min(far.x, min(far.y, far.z)));
+}
+
+// Smallest interval along the ray, clipped to [0, max_distance], covering every volume it hits.
+fn nebula_interval(origin: vec3<f32>, direction: vec3<f32>, max_distance: f32) -> vec2<f32> {
+    var interval = vec2<f32>(max_distance, 0.0);
+    for (var i: u32 = 0u; i < nebula_volumes.count; i = i + 1u) {
+        let volume_hit = volume_interval(nebula_volumes.volumes[i], origin, direction);
+        let clipped = vec2<f32>(max(volume_hit.x, 0.0), min(volume_hit.y, max_distance));
+        if clipped.x < clipped.y {
+            interval = vec2<f32>(min(interval.x, clipped.x), max(interval.y, clipped.y));
+        }
+    }
+    return interval;
+}
 
-fn noise(p: vec3<f32>, uv: vec2<f32>) -> vec4<f32> {
-    let time = settings.time;
-    let offset = vec3<f32>(0.0);
-    let point = p + offset;
-    let n1 = value_3d(point * settings.scale * 0.2 * vec3<f32>(0.75, 1.25, 0.75)) * vec4<f32>(0.8, 0.2, 1.0, 1.0);
-    let n2 = value_3d(point * settings.scale * 0.7 * vec3<f32>(1.25, 0.75, 1.25)) * vec4<f32>(0.1, 1.0, 0.2, 1.0);
-    let n4 = hash_31(point * settings.scale) * vec4<f32>(1.0, 1.0, 0.8, 1.0);
-    let noise_value = (n1 * 28 + n2 * 18 + n4 * 4) / 50.0;
-    return noise_value;
+fn henyey_greenstein(cos_theta: f32, g: f32) -> f32 {
+    let g2 = g * g;
+    let denominator = max(1.0 + g2 - 2.0 * g * cos_theta, 1e-4);
+    return (1.0 - g2) / (4.0 * PI * denominator * sqrt(denominator));
 }
 
-fn color_at_depth(x: f32) -> vec4<f32> {
-    return mix(vec4<f32>(0.0, 1.0, 0.0, 1.0), vec4<f32>(1.0, 0.0, 1.0, 1.0), x);
+// Transmittance from p towards the light through the nebula itself.
+fn light_transmittance(p: vec3<f32>) -> f32 {
+    let to_light = -settings.light_direction;
+    var optical_depth = 0.0;
+    for (var i: i32 = 1; i <= settings.shadow_step_count; i = i + 1) {
+        let sample_position = p + to_light * f32(i) * settings.shadow_step_distance;
+        optical_depth = optical_depth + density_at(sample_position) * settings.shadow_step_distance;
+    }
+    return exp(-optical_depth * (settings.absorption + settings.scattering));
+}
+
+fn camera_ray(uv: vec2<f32>) -> vec3<f32> {
+    let ndc = uv * 2.0 - 1.0;
+    return normalize(
+        settings.camera_forward
+            + settings.camera_right * ndc.x * settings.tan_half_fov * settings.aspect_ratio
+            + settings.camera_up * -ndc.y * settings.tan_half_fov
+    );
+}
+
+// Full resolution depth texel covered by a low resolution nebula texel.
+fn full_resolution_pixel(low_resolution_position: vec2<f32>) -> vec2<i32> {
+    let dimensions = vec2<i32>(textureDimensions(depth_texture));
+    let pixel = vec2<i32>(floor(low_resolution_position * settings.resolution_scale));
+    return clamp(pixel, vec2<i32>(0), dimensions - 1);
+}
+
+// Bevy uses an infinite reverse-z projection, so view space depth is near / depth.
+fn view_depth(pixel_position: vec2<i32>) -> f32 {
+    let depth = textureLoad(depth_texture, pixel_position, 0);
+    if depth <= 0.0 {
+        return 1e10;
+    }
+    return settings.near / depth;
+}
+
+// Distance along the ray to the first opaque surface, or a very large value for empty sky.
+fn scene_distance(pixel_position: vec2<i32>, ray_direction: vec3<f32>) -> f32 {
+    return view_depth(pixel_position) / max(dot(ray_direction, settings.camera_forward), 1e-4);
+}
+
+// Stands in for a blue-noise texture (Jimenez 2014). Like blue noise it has little low frequency
+// energy, so the banding it breaks up turns into fine grain that the temporal blend averages out,
+// without another texture binding. Each frame shifts it by the golden ratio, which spreads the
+// offsets of a pixel evenly over the 64 frame cycle.
+fn interleaved_gradient_noise(pixel_position: vec2<f32>) -> f32 {
+    return fract(52.9829189 * fract(dot(pixel_position, vec2<f32>(0.06711056, 0.00583715))));
 }
 
-fn ray_march(origin: vec3<f32>, ray_direction: vec3<f32>, uv: vec2<f32>) -> vec4<f32> {
-    var depth = 0.0;
-    var full_depth = f32(settings.step_count) * settings.step_distance;
-    var p = origin;
-    var result = vec4<f32>(0.0);
+// Returns in-scattered light in rgb and the remaining transmittance in a. Steps are spread over
+// the part of the ray inside the volumes, but never shorter than `step_distance`.
+fn ray_march(origin: vec3<f32>, ray_direction: vec3<f32>, interval: vec2<f32>, jitter: f32) -> vec4<f32> {
+    var scattered = vec3<f32>(0.0);
+    var transmittance = 1.0;
+    if interval.x >= interval.y {
+        return vec4<f32>(scattered, transmittance);
+    }
+    let step_distance = max((interval.y - interval.x) / f32(settings.step_count), settings.step_distance);
+    var depth = interval.x + jitter * step_distance;
+    var p = origin + depth * ray_direction;
+    let phase = henyey_greenstein(dot(settings.light_direction, -ray_direction), settings.anisotropy);
+    let light = settings.light_color * settings.light_intensity;
     for (var i: i32 = 0; i < settings.step_count; i = i + 1) {
-        var density = noise(p, uv);
-        if length(density) - settings.iso_value > 0.0 {
-            result = result + density * color_at_depth(depth / full_depth) * (1.0 / f32(settings.step_count));
+        if depth >= interval.y || transmittance < 0.01 {
+            break;
         }
-        depth = depth + settings.step_distance;
+        // The last step before a surface only covers part of its segment.
+        let step_length = min(step_distance, interval.y - depth);
+        let sample = sample_nebula(p);
+        let density = sample.a;
+        if density > 0.0 {
+            let extinction = density * (settings.absorption + settings.scattering);
+            let albedo = sample.rgb / density;
+            let in_scattering = (light * light_transmittance(p) * phase + settings.ambient_color)
+                * albedo * density * settings.scattering;
+            // Energy conserving integration of the scattering over the step (Hillaire 2015).
+            let step_transmittance = exp(-extinction * step_length);
+            scattered = scattered + transmittance * (in_scattering - in_scattering * step_transmittance) / extinction;
+            transmittance = transmittance * step_transmittance;
+        }
+        depth = depth + step_distance;
         p = origin + depth * ray_direction;
     }
-    return result;
+    return vec4<f32>(scattered, transmittance);
+}
+
+// Ray marches the nebula volumes at reduced resolution, offsetting each ray start by animated noise.
+@fragment
+fn march(in: FullscreenVertexOutput) -> @location(0) vec4<f32> {
+    let ray_direction = camera_ray(in.uv);
+    let max_distance = min(scene_distance(full_resolution_pixel(in.position.xy), ray_direction), settings.max_distance);
+    let interval = nebula_interval(settings.camera_position, ray_direction, max_distance);
+    let jitter = fract(interleaved_gradient_noise(in.position.xy) + f32(settings.frame_index % 64u) * 0.618034);
+    return ray_march(settings.camera_position, ray_direction, interval, jitter);
 }
 
+// Blends the new march result with last frame's result, reprojected through the previous camera.
 @fragment
-fn fragment(in: FullscreenVertexOutput) -> @location(0) vec4<f32> {
-    let ndc = in.uv * 2.0 - 1.0;
-    let ray_origin = settings.camera_position * settings.speed;
-    let ray_direction = normalize(
-        settings.camera_forward + settings.camera_right * ndc.x + settings.camera_up * -ndc.y
+fn temporal(in: FullscreenVertexOutput) -> @location(0) vec4<f32> {
+    let pixel_position = vec2<i32>(in.position.xy);
+    let dimensions = vec2<i32>(textureDimensions(march_texture));
+    let current = textureLoad(march_texture, pixel_position, 0);
+    var neighbourhood_min = current;
+    var neighbourhood_max = current;
+    for (var y: i32 = -1; y <= 1; y = y + 1) {
+        for (var x: i32 = -1; x <= 1; x = x + 1) {
+            let neighbour_position = clamp(pixel_position + vec2<i32>(x, y), vec2<i32>(0), dimensions - 1);
+            let neighbour = textureLoad(march_texture, neighbour_position, 0);
+            neighbourhood_min = min(neighbourhood_min, neighbour);
+            neighbourhood_max = max(neighbourhood_max, neighbour);
+        }
+    }
+
+    // Reproject through the middle of the nebula along the ray.
+    let ray_direction = camera_ray(in.uv);
+    let max_distance = min(scene_distance(full_resolution_pixel(in.position.xy), ray_direction), settings.max_distance);
+    let interval = nebula_interval(settings.camera_position, ray_direction, max_distance);
+    if interval.x >= interval.y {
+        return current;
+    }
+    let position = settings.camera_position + ray_direction * (interval.x + interval.y) * 0.5;
+    let previous_direction = position - settings.previous_camera_position;
+    let previous_z = dot(previous_direction, settings.previous_camera_forward);
+    if previous_z <= 0.0 {
+        return current;
+    }
+    let previous_ndc = vec2<f32>(
+        dot(previous_direction, settings.previous_camera_right) / (previous_z * settings.tan_half_fov * settings.aspect_ratio),
+        -dot(previous_direction, settings.previous_camera_up) / (previous_z * settings.tan_half_fov),
     );
-    let ray_color = ray_march(ray_origin, ray_direction, in.uv) * 0.15;
+    let previous_uv = previous_ndc * 0.5 + 0.5;
+    if any(previous_uv < vec2<f32>(0.0)) || any(previous_uv > vec2<f32>(1.0)) {
+        return current;
+    }
+    let history = textureSampleLevel(history_texture, nebula_sampler, previous_uv, 0.0);
+    // Freshly created history textures are all zeros and hold nothing to reuse.
+    if all(history == vec4<f32>(0.0)) {
+        return current;
+    }
+    let clamped_history = clamp(history, neighbourhood_min, neighbourhood_max);
+    return mix(current, clamped_history, settings.history_blend);
+}
+
+// Upsamples the accumulated nebula, weighting low resolution texels by how well their depth matches.
+@fragment
+fn composite(in: FullscreenVertexOutput) -> @location(0) vec4<f32> {
     let scene_color = textureSample(main_texture, main_texture_sampler, in.uv);
-    return ray_color + scene_color;
+    let dimensions = vec2<i32>(textureDimensions(nebula_texture));
+    let center_depth = view_depth(vec2<i32>(in.position.xy));
+    let low_resolution_position = in.position.xy / settings.resolution_scale - 0.5;
+    let base = vec2<i32>(floor(low_resolution_position));
+    let f = fract(low_resolution_position);
+
+    var nebula = vec4<f32>(0.0);
+    var weight_sum = 0.0;
+    var bilinear_nebula = vec4<f32>(0.0);
+    for (var y: i32 = 0; y <= 1; y = y + 1) {
+        for (var x: i32 = 0; x <= 1; x = x + 1) {
+            let texel = clamp(base + vec2<i32>(x, y), vec2<i32>(0), dimensions - 1);
+            let sample = textureLoad(nebula_texture, texel, 0);
+            let bilinear = select(1.0 - f.x, f.x, x == 1) * select(1.0 - f.y, f.y, y == 1);
+            let sample_depth = view_depth(full_resolution_pixel(vec2<f32>(texel) + 0.5));
+            let depth_weight = exp(-abs(center_depth - sample_depth) / (center_depth * 0.1));
+            nebula = nebula + sample * bilinear * depth_weight;
+            weight_sum = weight_sum + bilinear * depth_weight;
+            bilinear_nebula = bilinear_nebula + sample * bilinear;
+        }
+    }
+    nebula = select(bilinear_nebula, nebula / weight_sum, weight_sum > 1e-4);
+
+    return vec4<f32>(scene_color.rgb * nebula.a + nebula.rgb, scene_color.a);
 }
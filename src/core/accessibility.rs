@@ -0,0 +1,15 @@
+use bevy::prelude::*;
+
+/// Player preferences that turn off effects some people find uncomfortable.
+#[derive(Resource)]
+pub struct Accessibility {
+    pub chromatic_abberation: bool,
+}
+
+impl Default for Accessibility {
+    fn default() -> Self {
+        Self {
+            chromatic_abberation: true,
+        }
+    }
+}
@@ -0,0 +1,217 @@
+use bevy::{
+    pbr::{ExtendedMaterial, MaterialExtension},
+    prelude::*,
+    render::render_resource::{AsBindGroup, ShaderType},
+    shader::ShaderRef,
+};
+
+use crate::core::asteroid_mesh::LOD_SUBDIVISIONS;
+
+pub const ROCK_COLOR: Color = Color::srgb(0.25, 0.2, 0.15);
+
+/// Level of the asteroid drawn as an impostor rather than a mesh.
+pub const IMPOSTOR_LEVEL: usize = LOD_SUBDIVISIONS.len();
+
+/// Identifies an asteroid across chunk reloads.
+#[derive(Component, Clone, Copy)]
+pub struct AsteroidId {
+    pub chunk: IVec3,
+    /// Index of the field that generated the asteroid, in the field layout.
+    pub field: u32,
+    /// Order in which the field generates the asteroid in its chunk.
+    pub index: u32,
+}
+
+/// Which of its shape's meshes an asteroid draws, switched by distance to the camera.
+#[derive(Component)]
+pub struct AsteroidLod {
+    /// Index into the shapes of the asteroid's chunk in `AsteroidMeshes`.
+    pub variant: usize,
+    /// Index into the variant's level of detail meshes, or `IMPOSTOR_LEVEL`.
+    pub level: usize,
+}
+
+/// How much ore an asteroid holds. Richer tiers are tougher, yield more ore when destroyed and show
+/// more and brighter mineral veins.
+#[derive(Component, Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum OreTier {
+    Barren,
+    Common,
+    Rich,
+    Exotic,
+}
+
+impl OreTier {
+    pub const ALL: [OreTier; 4] = [
+        OreTier::Barren,
+        OreTier::Common,
+        OreTier::Rich,
+        OreTier::Exotic,
+    ];
+
+    /// Relative chance of an asteroid rolling this tier.
+    pub fn weight(self) -> f32 {
+        match self {
+            OreTier::Barren => 60.0,
+            OreTier::Common => 28.0,
+            OreTier::Rich => 10.0,
+            OreTier::Exotic => 2.0,
+        }
+    }
+
+    pub fn max_health(self) -> f32 {
+        match self {
+            OreTier::Barren => 20.0,
+            OreTier::Common => 30.0,
+            OreTier::Rich => 45.0,
+            OreTier::Exotic => 70.0,
+        }
+    }
+
+    /// Ore added to the player's cargo when the asteroid is destroyed.
+    pub fn ore_yield(self) -> f32 {
+        match self {
+            OreTier::Barren => 0.0,
+            OreTier::Common => 5.0,
+            OreTier::Rich => 15.0,
+            OreTier::Exotic => 50.0,
+        }
+    }
+
+    pub fn impostor(self) -> AsteroidImpostorMaterial {
+        let surface = self.surface().settings;
+        // The average color of rock and veins, since the veins are too small to see this far out.
+        let vein_coverage = surface.vein_amount * 0.25;
+        AsteroidImpostorMaterial {
+            color: ROCK_COLOR
+                .to_linear()
+                .mix(&surface.vein_color, vein_coverage),
+            emissive: surface.vein_emissive * vein_coverage,
+        }
+    }
+
+    pub fn surface(self) -> AsteroidSurface {
+        let settings = match self {
+            OreTier::Barren => AsteroidSurfaceSettings {
+                vein_color: LinearRgba::rgb(0.3, 0.28, 0.26),
+                vein_emissive: LinearRgba::BLACK,
+                vein_amount: 0.3,
+                vein_metallic: 0.0,
+                ..default()
+            },
+            OreTier::Common => AsteroidSurfaceSettings {
+                vein_color: LinearRgba::rgb(0.45, 0.2, 0.08),
+                vein_emissive: LinearRgba::BLACK,
+                vein_amount: 0.6,
+                vein_metallic: 0.6,
+                ..default()
+            },
+            OreTier::Rich => AsteroidSurfaceSettings {
+                vein_color: LinearRgba::rgb(0.9, 0.65, 0.2),
+                vein_emissive: LinearRgba::rgb(0.4, 0.25, 0.05),
+                vein_amount: 0.85,
+                vein_metallic: 1.0,
+                ..default()
+            },
+            OreTier::Exotic => AsteroidSurfaceSettings {
+                vein_color: LinearRgba::rgb(0.2, 0.8, 0.9),
+                vein_emissive: LinearRgba::rgb(1.0, 6.0, 8.0),
+                vein_amount: 1.0,
+                vein_metallic: 0.3,
+                ..default()
+            },
+        };
+        AsteroidSurface { settings }
+    }
+}
+
+/// Ore collected by destroying asteroids.
+#[derive(Component, Default)]
+pub struct Cargo {
+    pub ore: f32,
+}
+
+pub type AsteroidMaterial = ExtendedMaterial<StandardMaterial, AsteroidSurface>;
+
+/// Procedural rock detail and mineral veins on top of a `StandardMaterial`. Each asteroid's
+/// `MeshTag` seeds its variation, so all asteroids of a tier share one material and still differ.
+#[derive(Asset, AsBindGroup, Reflect, Clone)]
+pub struct AsteroidSurface {
+    #[uniform(100)]
+    pub settings: AsteroidSurfaceSettings,
+}
+
+#[derive(Reflect, Clone, Copy, ShaderType)]
+pub struct AsteroidSurfaceSettings {
+    pub vein_color: LinearRgba,
+    /// Glow of the veins. Values above one bloom.
+    pub vein_emissive: LinearRgba,
+    /// How much of the surface the veins cover, from none at zero to the most at one.
+    pub vein_amount: f32,
+    pub vein_metallic: f32,
+    /// Frequency of the vein network in world units.
+    pub vein_scale: f32,
+    /// Frequency of the triplanar rock detail in world units.
+    pub detail_scale: f32,
+    /// Strength of the normal perturbation from the rock detail.
+    pub bump_strength: f32,
+    /// Maximum relative change of each asteroid's base color.
+    pub color_variation: f32,
+    /// Maximum change of each asteroid's perceptual roughness.
+    pub roughness_variation: f32,
+}
+
+impl Default for AsteroidSurfaceSettings {
+    fn default() -> Self {
+        Self {
+            vein_color: LinearRgba::BLACK,
+            vein_emissive: LinearRgba::BLACK,
+            vein_amount: 0.0,
+            vein_metallic: 0.0,
+            vein_scale: 1.5,
+            detail_scale: 3.0,
+            bump_strength: 0.3,
+            color_variation: 0.3,
+            roughness_variation: 0.25,
+        }
+    }
+}
+
+impl MaterialExtension for AsteroidSurface {
+    fn fragment_shader() -> ShaderRef {
+        "shaders/asteroid.wgsl".into()
+    }
+}
+
+/// A camera-facing billboard standing in for a far asteroid, shaded like a lumpy sphere lit by the
+/// sun.
+#[derive(Asset, AsBindGroup, TypePath, Clone)]
+pub struct AsteroidImpostorMaterial {
+    #[uniform(0)]
+    pub color: LinearRgba,
+    #[uniform(1)]
+    pub emissive: LinearRgba,
+}
+
+impl Material for AsteroidImpostorMaterial {
+    fn vertex_shader() -> ShaderRef {
+        "shaders/asteroid_impostor.wgsl".into()
+    }
+
+    fn fragment_shader() -> ShaderRef {
+        "shaders/asteroid_impostor.wgsl".into()
+    }
+
+    fn prepass_vertex_shader() -> ShaderRef {
+        "shaders/asteroid_impostor.wgsl".into()
+    }
+
+    fn prepass_fragment_shader() -> ShaderRef {
+        "shaders/asteroid_impostor.wgsl".into()
+    }
+
+    // Masked, so the prepass runs the fragment shader and cuts out the silhouette too.
+    fn alpha_mode(&self) -> AlphaMode {
+        AlphaMode::Mask(0.5)
+    }
+}
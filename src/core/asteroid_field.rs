@@ -0,0 +1,186 @@
+use bevy::prelude::*;
+use rand::{distributions::WeightedIndex, prelude::Distribution, rngs::StdRng, Rng, SeedableRng};
+
+use crate::{
+    core::{
+        asteroid::OreTier,
+        distribution::{random_rotation, FieldShape},
+    },
+    resources::{asteroid_meshes::SHAPES_PER_CHUNK, world_seed::lattice_seed},
+};
+
+/// The asteroid fields of the world, loaded from a `.fields.ron` file. Fields may overlap, each
+/// generating its own asteroids.
+#[derive(Asset, Reflect, Clone)]
+pub struct FieldLayout {
+    pub fields: Vec<FieldConfig>,
+}
+
+#[derive(Reflect, Clone)]
+pub struct FieldConfig {
+    /// Names the field's random stream, so fields generate the same asteroids whatever other
+    /// fields are in the layout.
+    pub name: String,
+    /// Where in space the asteroids are.
+    pub shape: FieldShape,
+    /// Fewest and most asteroids generated in a chunk, where it lies entirely in the core of the
+    /// field's shape.
+    pub asteroids_per_chunk: UVec2,
+}
+
+/// An asteroid a field may generate in a chunk, before thinning it out by density and spacing.
+pub struct Candidate {
+    pub translation: Vec3,
+    pub rotation: Quat,
+    pub scale: Vec3,
+    pub tier: OreTier,
+    /// Which of the chunk's shapes it uses.
+    pub variant: usize,
+    /// Seeds the surface variation in `asteroid.wgsl`.
+    pub tag: u32,
+    /// Kept where this is below the field's density.
+    roll: f32,
+    /// Of two candidates closer than the field's spacing, the one with the higher priority is kept.
+    priority: u64,
+}
+
+impl FieldConfig {
+    /// The asteroids the field generates in the chunk at `coord`, with their index in the chunk.
+    /// `seed` is the field's seed.
+    ///
+    /// Spacing doesn't place asteroids one by one, which would make a chunk depend on which of its
+    /// neighbours loaded first. Instead a candidate is dropped if one of higher priority, also kept
+    /// by the density, is within the spacing, in this chunk or a neighbouring one. That only
+    /// depends on the candidates, so both sides of a chunk border always agree.
+    pub fn asteroids(&self, seed: u64, chunk_size: f32, coord: IVec3) -> Vec<(u32, Candidate)> {
+        let kept_by_density = |candidate: &Candidate| {
+            candidate.roll < self.shape.density(seed, candidate.translation)
+        };
+        let candidates = (0..).zip(self.candidates(seed, chunk_size, coord));
+        let Some(spacing) = self.shape.spacing() else {
+            return candidates
+                .filter(|(_, candidate)| kept_by_density(candidate))
+                .collect();
+        };
+
+        // Only candidates this close to the chunk can be within the spacing of its asteroids.
+        let center = (coord.as_vec3() + 0.5) * chunk_size;
+        let half_extents = Vec3::splat(chunk_size / 2.0 + spacing);
+        let rivals: Vec<(Vec3, u64)> = (-1..=1)
+            .flat_map(|x| (-1..=1).flat_map(move |y| (-1..=1).map(move |z| IVec3::new(x, y, z))))
+            .flat_map(|offset| self.candidates(seed, chunk_size, coord + offset))
+            .filter(|rival| (rival.translation - center).abs().cmple(half_extents).all())
+            .filter(|rival| kept_by_density(rival))
+            .map(|rival| (rival.translation, rival.priority))
+            .collect();
+        candidates
+            .filter(|(_, candidate)| {
+                kept_by_density(candidate)
+                    && rivals.iter().all(|(translation, priority)| {
+                        *priority <= candidate.priority
+                            || translation.distance(candidate.translation) >= spacing
+                    })
+            })
+            .collect()
+    }
+
+    /// Every candidate of the chunk at `coord`, in order. Everything about a candidate is drawn
+    /// before any is thinned out, so the remaining asteroids come out the same.
+    fn candidates(&self, seed: u64, chunk_size: f32, coord: IVec3) -> Vec<Candidate> {
+        let mut rng = StdRng::seed_from_u64(lattice_seed(seed, coord));
+        let tier_distribution = WeightedIndex::new(OreTier::ALL.map(OreTier::weight))
+            .expect("ore tier weights are valid");
+        let origin = coord.as_vec3() * chunk_size;
+        let count = rng.gen_range(self.asteroids_per_chunk.x..=self.asteroids_per_chunk.y);
+        (0..count)
+            .map(|_| Candidate {
+                translation: origin + random_vec3(&mut rng, 0.0, chunk_size),
+                rotation: random_rotation(&mut rng),
+                scale: random_vec3(&mut rng, 0.75, 1.25),
+                tier: OreTier::ALL[tier_distribution.sample(&mut rng)],
+                variant: rng.gen_range(0..SHAPES_PER_CHUNK),
+                tag: rng.r#gen(),
+                roll: rng.r#gen(),
+                priority: rng.r#gen(),
+            })
+            .collect()
+    }
+}
+
+fn random_vec3(rng: &mut impl Rng, min: f32, max: f32) -> Vec3 {
+    Vec3::new(
+        rng.gen_range(min..max),
+        rng.gen_range(min..max),
+        rng.gen_range(min..max),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use std::any::TypeId;
+
+    use bevy::{
+        asset::ron,
+        reflect::{serde::TypedReflectDeserializer, TypeRegistry},
+    };
+
+    use super::*;
+
+    const CHUNK_SIZE: f32 = 100.0;
+
+    #[test]
+    fn clusters_stay_spaced_across_chunk_borders() {
+        let field = FieldConfig {
+            name: "clusters".to_owned(),
+            shape: FieldShape::Clusters {
+                cell_size: 150.0,
+                radius: Vec2::new(100.0, 150.0),
+                chance: 1.0,
+                spacing: 15.0,
+            },
+            asteroids_per_chunk: UVec2::new(200, 200),
+        };
+        let asteroids: Vec<Vec3> = [IVec3::ZERO, IVec3::X, IVec3::Y, IVec3::ONE]
+            .into_iter()
+            .flat_map(|coord| field.asteroids(7, CHUNK_SIZE, coord))
+            .map(|(_, asteroid)| asteroid.translation)
+            .collect();
+        assert!(asteroids.len() > 50, "{}", asteroids.len());
+        for (i, a) in asteroids.iter().enumerate() {
+            for b in &asteroids[i + 1..] {
+                assert!(a.distance(*b) >= 15.0, "{a} and {b} are too close");
+            }
+        }
+    }
+
+    #[test]
+    fn fields_without_spacing_keep_dense_candidates() {
+        let field = FieldConfig {
+            name: "volume".to_owned(),
+            shape: FieldShape::Volume,
+            asteroids_per_chunk: UVec2::new(10, 20),
+        };
+        let asteroids = field.asteroids(7, CHUNK_SIZE, IVec3::ZERO);
+        assert!((10..=20).contains(&asteroids.len()));
+        let again = field.asteroids(7, CHUNK_SIZE, IVec3::ZERO);
+        for ((index, asteroid), (again_index, again)) in asteroids.iter().zip(&again) {
+            assert_eq!(index, again_index);
+            assert_eq!(asteroid.translation, again.translation);
+        }
+    }
+
+    #[test]
+    fn the_world_layout_parses() {
+        let mut registry = TypeRegistry::default();
+        registry.register::<FieldLayout>();
+        let registration = registry.get(TypeId::of::<FieldLayout>()).unwrap();
+        let reflected = ron::Options::default()
+            .from_str_seed(
+                include_str!("../../assets/fields/world.fields.ron"),
+                TypedReflectDeserializer::new(registration, &registry),
+            )
+            .unwrap();
+        let layout = FieldLayout::from_reflect(reflected.as_partial_reflect()).unwrap();
+        assert!(layout.fields.len() > 1);
+    }
+}
@@ -0,0 +1,194 @@
+use avian3d::prelude::Collider;
+use bevy::{
+    asset::RenderAssetUsages,
+    mesh::{Indices, Mesh, PrimitiveTopology, VertexAttributeValues},
+    prelude::*,
+};
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+use crate::{core::distribution::random_direction, noise::fbm::fbm_3d};
+
+/// Icosphere subdivisions of each level of detail, from the closest to the farthest.
+pub const LOD_SUBDIVISIONS: [u32; 3] = [4, 2, 1];
+/// Icosphere subdivisions of the points the collider's convex hull is built from.
+const COLLIDER_SUBDIVISIONS: u32 = 2;
+/// Half size of the impostor mesh's bounding box. `asteroid_impostor.wgsl` sizes the billboard
+/// itself, this only has to be large enough that frustum culling never hides a visible impostor.
+const IMPOSTOR_BOUNDS: f32 = 2.0;
+/// Width of a crater's raised rim, relative to the crater's radius.
+const CRATER_RIM_WIDTH: f32 = 0.3;
+/// Depth of a crater's flat floor, relative to the crater's depth.
+const CRATER_FLOOR: f32 = 0.7;
+
+struct Crater {
+    /// Unit vector to the crater's center.
+    center: Vec3,
+    /// Distance from the center to the rim, measured on the unit sphere.
+    radius: f32,
+    depth: f32,
+    rim_height: f32,
+}
+
+/// A lumpy, cratered rock around the origin with a mean radius of about one. Every shape parameter
+/// is derived from the seed, so the same seed always builds the same meshes and collider.
+pub struct AsteroidShape {
+    seed_offset: Vec3,
+    /// Per axis scale, so the rock isn't round.
+    stretch: Vec3,
+    /// Frequency of the surface noise on the unit sphere.
+    frequency: f32,
+    /// Height of the surface noise relative to the radius.
+    roughness: f32,
+    craters: Vec<Crater>,
+}
+
+impl AsteroidShape {
+    pub fn from_seed(seed: u64) -> Self {
+        let mut rng = StdRng::seed_from_u64(seed);
+        let offset_range = -1000.0..1000.0;
+        let seed_offset = Vec3::new(
+            rng.gen_range(offset_range.clone()),
+            rng.gen_range(offset_range.clone()),
+            rng.gen_range(offset_range),
+        );
+        let stretch = Vec3::new(
+            rng.gen_range(0.8..1.3),
+            rng.gen_range(0.7..1.1),
+            rng.gen_range(0.6..1.0),
+        );
+        let craters = (0..rng.gen_range(2..8))
+            .map(|_| {
+                let radius = rng.gen_range(0.15..0.6);
+                Crater {
+                    center: random_direction(&mut rng),
+                    radius,
+                    depth: radius * rng.gen_range(0.2..0.4),
+                    rim_height: radius * rng.gen_range(0.02..0.06),
+                }
+            })
+            .collect();
+
+        Self {
+            seed_offset,
+            stretch,
+            frequency: rng.gen_range(1.0..2.0),
+            roughness: rng.gen_range(0.25..0.45),
+            craters,
+        }
+    }
+
+    /// Distance from the center to the surface along the unit vector `direction`, before
+    /// stretching.
+    pub fn radius(&self, direction: Vec3) -> f32 {
+        let noise = fbm_3d(direction * self.frequency + self.seed_offset, 5, 2.0, 0.5);
+        let mut radius = 1.0 + self.roughness * noise;
+        for crater in &self.craters {
+            let x = direction.distance(crater.center) / crater.radius;
+            // A bowl with a flat floor inside the crater, and a rim around its edge.
+            let bowl = (x * x - 1.0).clamp(-CRATER_FLOOR, 0.0);
+            let rim = (-((x - 1.0) / CRATER_RIM_WIDTH).powi(2)).exp();
+            radius += crater.depth * bowl + crater.rim_height * rim;
+        }
+        radius
+    }
+
+    pub fn surface_point(&self, direction: Vec3) -> Vec3 {
+        direction * self.radius(direction) * self.stretch
+    }
+
+    /// A displaced icosphere with smooth normals. It has no UVs, since the asteroid material is
+    /// triplanar.
+    pub fn mesh(&self, subdivisions: u32) -> Mesh {
+        let mut mesh = Sphere::new(1.0)
+            .mesh()
+            .ico(subdivisions)
+            .expect("asteroid subdivisions are in range");
+        mesh.remove_attribute(Mesh::ATTRIBUTE_UV_0);
+        if let Some(VertexAttributeValues::Float32x3(positions)) =
+            mesh.attribute_mut(Mesh::ATTRIBUTE_POSITION)
+        {
+            for position in positions.iter_mut() {
+                *position = self
+                    .surface_point(Vec3::from(*position).normalize())
+                    .to_array();
+            }
+        }
+        mesh.compute_smooth_normals();
+        mesh
+    }
+
+    /// Level of detail meshes, one for each entry of `LOD_SUBDIVISIONS`.
+    pub fn lod_meshes(&self) -> [Mesh; LOD_SUBDIVISIONS.len()] {
+        LOD_SUBDIVISIONS.map(|subdivisions| self.mesh(subdivisions))
+    }
+
+    /// The convex hull of a coarse version of the surface. Craters are filled in.
+    pub fn collider(&self) -> Collider {
+        Collider::convex_hull_from_mesh(&self.mesh(COLLIDER_SUBDIVISIONS))
+            .unwrap_or_else(|| Collider::sphere(1.0))
+    }
+}
+
+/// A quad for far asteroids, which `asteroid_impostor.wgsl` turns to face the camera using only the
+/// signs of its corners' x and y. The corners alternate in z so the bounding box covers the
+/// billboard in any orientation.
+pub fn impostor_mesh() -> Mesh {
+    let corners = [
+        [-1.0, -1.0, -1.0],
+        [1.0, -1.0, 1.0],
+        [1.0, 1.0, -1.0],
+        [-1.0, 1.0, 1.0],
+    ];
+    Mesh::new(
+        PrimitiveTopology::TriangleList,
+        RenderAssetUsages::default(),
+    )
+    .with_inserted_attribute(
+        Mesh::ATTRIBUTE_POSITION,
+        corners
+            .map(|corner| (Vec3::from(corner) * IMPOSTOR_BOUNDS).to_array())
+            .to_vec(),
+    )
+    .with_inserted_indices(Indices::U16(vec![0, 1, 2, 0, 2, 3]))
+}
+
+#[cfg(test)]
+mod tests {
+    use avian3d::parry::shape::ShapeType;
+
+    use super::*;
+
+    fn positions(mesh: &Mesh) -> Vec<[f32; 3]> {
+        match mesh.attribute(Mesh::ATTRIBUTE_POSITION) {
+            Some(VertexAttributeValues::Float32x3(positions)) => positions.clone(),
+            _ => panic!("asteroid meshes have positions"),
+        }
+    }
+
+    #[test]
+    fn shapes_are_deterministic() {
+        for seed in [0, 1, 0x5eed, u64::MAX] {
+            let mesh = AsteroidShape::from_seed(seed).mesh(2);
+            assert_eq!(
+                positions(&mesh),
+                positions(&AsteroidShape::from_seed(seed).mesh(2))
+            );
+        }
+        assert_ne!(
+            positions(&AsteroidShape::from_seed(1).mesh(2)),
+            positions(&AsteroidShape::from_seed(2).mesh(2))
+        );
+    }
+
+    #[test]
+    fn colliders_are_convex_hulls() {
+        for seed in 0..32 {
+            let collider = AsteroidShape::from_seed(seed).collider();
+            assert_eq!(
+                collider.shape().shape_type(),
+                ShapeType::ConvexPolyhedron,
+                "seed {seed} fell back to a sphere"
+            );
+        }
+    }
+}
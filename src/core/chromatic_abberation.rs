@@ -0,0 +1,56 @@
+use bevy::prelude::*;
+
+/// Adds a short burst of chromatic abberation, e.g. when dashing.
+#[derive(Message)]
+pub struct AbberationPulse(pub f32);
+
+#[derive(Clone, Copy, PartialEq, Eq, Default)]
+pub enum ChromaticAbberationMode {
+    /// Color channels split away from the screen center.
+    #[default]
+    Radial,
+    /// Color channels split along the player's motion on screen.
+    Directional,
+}
+
+impl ChromaticAbberationMode {
+    pub fn next(self) -> Self {
+        match self {
+            ChromaticAbberationMode::Radial => ChromaticAbberationMode::Directional,
+            ChromaticAbberationMode::Directional => ChromaticAbberationMode::Radial,
+        }
+    }
+}
+
+/// Drives the chromatic abberation intensity from gameplay.
+#[derive(Component)]
+pub struct ChromaticAbberationController {
+    pub mode: ChromaticAbberationMode,
+    pub base_intensity: f32,
+    pub max_intensity: f32,
+    /// Pulse added per point of damage taken by the player.
+    pub damage_pulse: f32,
+    /// How fast pulses fade, in intensity per second.
+    pub decay: f32,
+    /// Health fraction below which the abberation throbs.
+    pub low_health_threshold: f32,
+    pub low_health_intensity: f32,
+    pub low_health_frequency: f32,
+    pub pulse: f32,
+}
+
+impl Default for ChromaticAbberationController {
+    fn default() -> Self {
+        Self {
+            mode: ChromaticAbberationMode::default(),
+            base_intensity: 0.01,
+            max_intensity: 0.08,
+            damage_pulse: 0.002,
+            decay: 0.1,
+            low_health_threshold: 0.3,
+            low_health_intensity: 0.02,
+            low_health_frequency: 1.2,
+            pulse: 0.0,
+        }
+    }
+}
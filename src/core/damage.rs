@@ -0,0 +1,19 @@
+use bevy::prelude::*;
+
+#[derive(Message)]
+pub struct Damage {
+    pub target: Entity,
+    pub amount: f32,
+}
+
+#[derive(Component)]
+pub struct Invulnerable {
+    pub remaining: f32,
+}
+
+/// Sent when an entity's health runs out, just before it is despawned.
+#[derive(Message)]
+pub struct Destroyed {
+    pub transform: Transform,
+    pub velocity: Vec3,
+}
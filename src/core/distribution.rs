@@ -0,0 +1,210 @@
+use std::f32::consts::TAU;
+
+use bevy::{
+    math::cubic_splines::{CubicCardinalSpline, CubicGenerator},
+    prelude::*,
+};
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+use crate::resources::world_seed::{lattice_seed, splitmix_64};
+
+/// Straight pieces each segment of a stream's spline is measured with.
+const STREAM_SUBDIVISIONS: usize = 16;
+
+/// Uniformly distributed on the unit sphere. By Archimedes' hat-box theorem, a uniform height
+/// gives a uniform area, where a uniform polar angle would bunch points up at the poles.
+pub fn random_direction(rng: &mut impl Rng) -> Vec3 {
+    let z: f32 = rng.gen_range(-1.0..1.0);
+    let angle = rng.gen_range(0.0..TAU);
+    let radius = (1.0 - z * z).sqrt();
+    Vec3::new(radius * angle.cos(), radius * angle.sin(), z)
+}
+
+// Uniformly distributed, from Shoemake's "Uniform random rotations".
+pub fn random_rotation(rng: &mut impl Rng) -> Quat {
+    let (u1, u2, u3): (f32, f32, f32) = (rng.r#gen(), rng.r#gen(), rng.r#gen());
+    let (a, b) = ((1.0 - u1).sqrt(), u1.sqrt());
+    Quat::from_xyzw(
+        a * (TAU * u2).sin(),
+        a * (TAU * u2).cos(),
+        b * (TAU * u3).sin(),
+        b * (TAU * u3).cos(),
+    )
+}
+
+/// Where an asteroid field places its asteroids. Density is highest at a shape's core and falls
+/// off towards its edge, so shapes don't end in a hard wall.
+#[derive(Reflect, Clone, Debug)]
+pub enum FieldShape {
+    /// Fills all of space.
+    Volume,
+    /// A flat ring around a body, such as a planet's rings or a star's asteroid belt.
+    Belt {
+        center: Vec3,
+        /// Axis the belt turns around.
+        normal: Dir3,
+        /// Distance from the center to the middle of the belt.
+        radius: f32,
+        /// Half the belt's extent across its radius.
+        width: f32,
+        /// Half the belt's extent along its normal.
+        thickness: f32,
+    },
+    /// Round clusters, at most one in each cell of a lattice, with asteroids spaced out inside
+    /// them.
+    Clusters {
+        /// Edge length of the lattice cells. At least the largest cluster radius, so a point is only
+        /// ever inside clusters of its own or neighbouring cells.
+        cell_size: f32,
+        /// Smallest and largest cluster radius.
+        radius: Vec2,
+        /// Chance of a cell holding a cluster.
+        chance: f32,
+        /// Smallest distance between two asteroids, for Poisson-disk spacing.
+        spacing: f32,
+    },
+    /// A tube along a Catmull-Rom spline through `points`, such as debris trailing a comet.
+    Stream { points: Vec<Vec3>, radius: f32 },
+    /// A hollow sphere.
+    Shell {
+        center: Vec3,
+        inner_radius: f32,
+        outer_radius: f32,
+    },
+}
+
+impl FieldShape {
+    /// Chance of keeping an asteroid at `position`, from 0 outside the shape to 1 at its core.
+    /// `seed` places the clusters.
+    pub fn density(&self, seed: u64, position: Vec3) -> f32 {
+        // Distance from the shape's core, relative to the distance from the core to its edge.
+        let edge_distance = match self {
+            FieldShape::Volume => 0.0,
+            FieldShape::Belt {
+                center,
+                normal,
+                radius,
+                width,
+                thickness,
+            } => {
+                let offset = position - *center;
+                let height = offset.dot(normal.as_vec3());
+                let radial = (offset - height * normal.as_vec3()).length() - radius;
+                Vec2::new(radial / width, height / thickness).length()
+            }
+            FieldShape::Clusters {
+                cell_size,
+                radius,
+                chance,
+                ..
+            } => {
+                let cell = (position / *cell_size).floor().as_ivec3();
+                let seed = splitmix_64(seed);
+                (-1..=1)
+                    .flat_map(|x| (-1..=1).flat_map(move |y| (-1..=1).map(move |z| (x, y, z))))
+                    .filter_map(|(x, y, z)| {
+                        let neighbour = cell + IVec3::new(x, y, z);
+                        let mut rng = StdRng::seed_from_u64(lattice_seed(seed, neighbour));
+                        let offset = Vec3::new(rng.r#gen(), rng.r#gen(), rng.r#gen());
+                        let center = (neighbour.as_vec3() + offset) * *cell_size;
+                        let cluster_radius = rng.gen_range(radius.x..=radius.y);
+                        (rng.r#gen::<f32>() < *chance)
+                            .then(|| position.distance(center) / cluster_radius)
+                    })
+                    .fold(f32::INFINITY, f32::min)
+            }
+            FieldShape::Stream { points, radius } => {
+                let Ok(curve) =
+                    CubicCardinalSpline::new_catmull_rom(points.iter().copied()).to_curve()
+                else {
+                    return 0.0;
+                };
+                let samples: Vec<Vec3> = curve
+                    .iter_positions(curve.segments().len() * STREAM_SUBDIVISIONS)
+                    .collect();
+                samples
+                    .windows(2)
+                    .map(|pair| Segment3d::new(pair[0], pair[1]).closest_point(position))
+                    .map(|closest| position.distance(closest))
+                    .fold(f32::INFINITY, f32::min)
+                    / radius
+            }
+            FieldShape::Shell {
+                center,
+                inner_radius,
+                outer_radius,
+            } => {
+                let middle = (inner_radius + outer_radius) / 2.0;
+                let half_width = (outer_radius - inner_radius) / 2.0;
+                (position.distance(*center) - middle).abs() / half_width
+            }
+        };
+        (1.0 - edge_distance * edge_distance).max(0.0)
+    }
+
+    /// Smallest distance between asteroids, for shapes that space them out.
+    pub fn spacing(&self) -> Option<f32> {
+        match self {
+            FieldShape::Clusters { spacing, .. } => Some(*spacing),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SAMPLES: usize = 100_000;
+
+    #[test]
+    fn directions_cover_the_sphere_evenly() {
+        let mut rng = StdRng::seed_from_u64(0);
+        // Equal height bands have equal areas, so each should get an equal share.
+        let mut bands = [0usize; 8];
+        for _ in 0..SAMPLES {
+            let direction = random_direction(&mut rng);
+            assert!((direction.length() - 1.0).abs() < 1e-4);
+            let band = ((direction.z + 1.0) / 2.0 * bands.len() as f32) as usize;
+            bands[band.min(bands.len() - 1)] += 1;
+        }
+        let expected = SAMPLES / bands.len();
+        for count in bands {
+            assert!(count.abs_diff(expected) < expected / 20, "{bands:?}");
+        }
+    }
+
+    #[test]
+    fn shapes_are_dense_inside_and_empty_outside() {
+        let belt = FieldShape::Belt {
+            center: Vec3::ZERO,
+            normal: Dir3::Y,
+            radius: 100.0,
+            width: 20.0,
+            thickness: 5.0,
+        };
+        assert_eq!(belt.density(0, Vec3::new(0.0, 0.0, 100.0)), 1.0);
+        assert_eq!(belt.density(0, Vec3::ZERO), 0.0);
+        assert_eq!(belt.density(0, Vec3::new(100.0, 10.0, 0.0)), 0.0);
+
+        let stream = FieldShape::Stream {
+            points: vec![
+                Vec3::ZERO,
+                Vec3::new(100.0, 0.0, 0.0),
+                Vec3::new(200.0, 50.0, 0.0),
+            ],
+            radius: 10.0,
+        };
+        assert!(stream.density(0, Vec3::new(100.0, 0.0, 0.0)) > 0.99);
+        assert_eq!(stream.density(0, Vec3::new(100.0, 50.0, 0.0)), 0.0);
+
+        let shell = FieldShape::Shell {
+            center: Vec3::ZERO,
+            inner_radius: 50.0,
+            outer_radius: 70.0,
+        };
+        assert_eq!(shell.density(0, Vec3::new(0.0, 60.0, 0.0)), 1.0);
+        assert_eq!(shell.density(0, Vec3::new(0.0, 40.0, 0.0)), 0.0);
+        assert_eq!(shell.density(0, Vec3::new(0.0, 80.0, 0.0)), 0.0);
+    }
+}
@@ -0,0 +1,12 @@
+use bevy::prelude::*;
+
+/// Requests a short-lived point light that fades out over `duration` seconds.
+#[derive(Message)]
+pub struct LightFlash {
+    pub position: Vec3,
+    pub color: Color,
+    /// Peak intensity in lumens.
+    pub intensity: f32,
+    pub range: f32,
+    pub duration: f32,
+}
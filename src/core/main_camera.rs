@@ -1,4 +1,107 @@
-use bevy::prelude::Component;
+use bevy::prelude::*;
 
 #[derive(Component)]
 pub struct MainCamera;
+
+#[derive(Component, Default, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CameraMode {
+    #[default]
+    Chase,
+    Cockpit,
+    Orbit,
+    Cinematic,
+}
+
+impl CameraMode {
+    pub fn next(self) -> Self {
+        match self {
+            CameraMode::Chase => CameraMode::Cockpit,
+            CameraMode::Cockpit => CameraMode::Orbit,
+            CameraMode::Orbit => CameraMode::Cinematic,
+            CameraMode::Cinematic => CameraMode::Chase,
+        }
+    }
+
+    /// Whether the mouse is turning the orbit camera, rather than the ship.
+    pub fn is_orbiting(self, keyboard_input: &ButtonInput<KeyCode>) -> bool {
+        self == CameraMode::Orbit && keyboard_input.pressed(KeyCode::AltLeft)
+    }
+}
+
+#[derive(Component, Debug)]
+pub struct CameraOrbit {
+    pub yaw: f32,
+    pub pitch: f32,
+    pub distance: f32,
+}
+
+impl Default for CameraOrbit {
+    fn default() -> Self {
+        Self {
+            yaw: 0.0,
+            pitch: -0.2,
+            distance: 18.0,
+        }
+    }
+}
+
+#[derive(Component, Debug)]
+pub struct CameraSpring {
+    pub stiffness: Vec3,
+    pub rotation_stiffness: f32,
+    pub look_ahead: f32,
+    pub reference_speed: f32,
+    pub base_fov: f32,
+    pub max_fov: f32,
+    pub max_distance_scale: f32,
+    pub velocity: Vec3,
+}
+
+impl Default for CameraSpring {
+    fn default() -> Self {
+        Self {
+            stiffness: Vec3::new(8.0, 6.0, 4.0),
+            rotation_stiffness: 8.0,
+            look_ahead: 0.15,
+            reference_speed: 60.0,
+            base_fov: std::f32::consts::FRAC_PI_4,
+            max_fov: std::f32::consts::FRAC_PI_3,
+            max_distance_scale: 1.4,
+            velocity: Vec3::ZERO,
+        }
+    }
+}
+
+impl CameraSpring {
+    pub fn speed_factor(&self, speed: f32) -> f32 {
+        (speed / self.reference_speed).clamp(0.0, 1.0)
+    }
+}
+
+#[derive(Message)]
+pub struct CameraTrauma(pub f32);
+
+#[derive(Component, Debug)]
+pub struct CameraShake {
+    pub trauma: f32,
+    pub decay: f32,
+    pub frequency: f32,
+    pub max_offset: Vec2,
+    pub max_rotation: Vec3,
+    pub applied_offset: Vec3,
+    pub applied_rotation: Quat,
+}
+
+impl Default for CameraShake {
+    fn default() -> Self {
+        Self {
+            trauma: 0.0,
+            decay: 1.2,
+            frequency: 18.0,
+            max_offset: Vec2::new(0.6, 0.4),
+            max_rotation: Vec3::new(0.03, 0.03, 0.08),
+            applied_offset: Vec3::ZERO,
+            applied_rotation: Quat::IDENTITY,
+        }
+    }
+}
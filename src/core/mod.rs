@@ -1,4 +1,17 @@
+pub mod accessibility;
+pub mod asteroid;
+pub mod asteroid_field;
+pub mod asteroid_mesh;
+pub mod chromatic_abberation;
+pub mod damage;
+pub mod distribution;
+pub mod lighting;
 pub mod main_camera;
+pub mod nebula;
+pub mod particles;
 pub mod player;
 pub mod projectile;
-// pub mod stats;
+pub mod quality;
+pub mod stats;
+pub mod target;
+pub mod weapon;
@@ -0,0 +1,64 @@
+use bevy::{prelude::*, render::render_resource::ShaderType};
+
+#[derive(Component)]
+pub struct NebulaExposure {
+    pub density: f32,
+    pub sensor_factor: f32,
+}
+
+impl Default for NebulaExposure {
+    fn default() -> Self {
+        Self {
+            density: 0.0,
+            sensor_factor: 1.0,
+        }
+    }
+}
+
+/// Up to four colors, sampled by nebula density at the matching `stops`.
+#[derive(Default, Clone, Copy, ShaderType)]
+pub struct NebulaGradient {
+    pub colors: [Vec4; 4],
+    pub stops: Vec4,
+}
+
+/// Bounds of a nebula volume in its local space.
+#[derive(Clone, Copy)]
+pub enum NebulaShape {
+    Sphere { radius: f32 },
+    Box { half_extents: Vec3 },
+}
+
+/// A bounded nebula cloud, placed, rotated and scaled by its transform.
+#[derive(Component, Clone, Copy)]
+#[require(Transform)]
+pub struct NebulaVolume {
+    pub shape: NebulaShape,
+    pub density: f32,
+    pub gradient: NebulaGradient,
+    pub noise_scale: f32,
+    pub animation_speed: f32,
+    /// Fraction of the bounds over which the density fades out towards the edge.
+    pub edge_falloff: f32,
+}
+
+impl Default for NebulaVolume {
+    fn default() -> Self {
+        Self {
+            shape: NebulaShape::Sphere { radius: 100.0 },
+            density: 1.0,
+            gradient: NebulaGradient {
+                colors: [
+                    Vec4::new(0.1, 0.8, 0.3, 1.0),
+                    Vec4::new(0.3, 0.5, 0.8, 1.0),
+                    Vec4::new(0.8, 0.2, 0.9, 1.0),
+                    Vec4::new(1.0, 0.6, 0.9, 1.0),
+                ],
+                stops: Vec4::new(0.0, 0.3, 0.6, 1.0),
+            },
+            noise_scale: 0.1,
+            animation_speed: 0.0,
+            edge_falloff: 0.3,
+        }
+    }
+}
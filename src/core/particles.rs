@@ -0,0 +1,84 @@
+use bevy::prelude::*;
+
+/// A particle effect, loaded from a `.particle.ron` file.
+#[derive(Asset, Reflect, Clone)]
+pub struct ParticleEffect {
+    /// Particles emitted per second while the emitter runs.
+    pub rate: f32,
+    /// Particles emitted at once when the emitter starts.
+    pub burst: u32,
+    /// Seconds the emitter runs before it despawns, or forever if zero.
+    pub duration: f32,
+    /// Minimum and maximum particle lifetime in seconds.
+    pub lifetime: Vec2,
+    /// Minimum and maximum initial speed.
+    pub speed: Vec2,
+    /// Half angle in radians of the emission cone around the emitter's forward axis.
+    pub spread: f32,
+    /// Fraction of the emitter's velocity added to new particles.
+    pub inherit_velocity: f32,
+    pub drag: f32,
+    /// Particle size at birth and at death.
+    pub size: Vec2,
+    /// Maximum spin in radians per second.
+    pub angular_speed: f32,
+    /// Color at birth. Glowing shapes above one bloom.
+    pub start_color: LinearRgba,
+    pub end_color: LinearRgba,
+    pub shape: ParticleShape,
+}
+
+#[derive(Reflect, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ParticleShape {
+    /// A glowing ball, for fire and exhaust.
+    Orb,
+    /// A glowing streak stretched along its velocity.
+    Spark,
+    /// A lit, solid chunk.
+    Debris,
+}
+
+/// Emits particles of `effect` along its forward axis.
+#[derive(Component)]
+#[require(Transform)]
+pub struct ParticleEmitter {
+    pub effect: Handle<ParticleEffect>,
+    /// Scales the emission rate.
+    pub intensity: f32,
+    /// Velocity inherited by new particles. Emitters on a physics body follow the body's velocity.
+    pub velocity: Vec3,
+    pub elapsed: f32,
+    pub pending: f32,
+    pub burst_emitted: bool,
+}
+
+impl ParticleEmitter {
+    pub fn new(effect: Handle<ParticleEffect>) -> Self {
+        Self {
+            effect,
+            intensity: 1.0,
+            velocity: Vec3::ZERO,
+            elapsed: 0.0,
+            pending: 0.0,
+            burst_emitted: false,
+        }
+    }
+}
+
+#[derive(Component)]
+pub struct Particle {
+    pub effect: AssetId<ParticleEffect>,
+    pub velocity: Vec3,
+    pub angular_velocity: Vec3,
+    pub age: f32,
+    pub lifetime: f32,
+    pub color_step: usize,
+}
+
+/// Plays `effect` once at `transform`.
+#[derive(Message)]
+pub struct SpawnParticles {
+    pub effect: Handle<ParticleEffect>,
+    pub transform: Transform,
+    pub velocity: Vec3,
+}
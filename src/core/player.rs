@@ -1,4 +1,7 @@
-use bevy::ecs::component::Component;
+use bevy::{ecs::component::Component, math::Vec3};
+
+/// Where the engine nozzle sits on the spaceship mesh.
+pub const ENGINE_OFFSET: Vec3 = Vec3::new(0.0, 0.1, 1.4);
 
 #[derive(Component)]
 pub struct Player;
@@ -3,4 +3,5 @@ use bevy::prelude::*;
 #[derive(Component)]
 pub struct Projectile {
     pub lifetime: f32,
+    pub damage: f32,
 }
@@ -0,0 +1,103 @@
+use bevy::prelude::*;
+
+use crate::core::asteroid_mesh::LOD_SUBDIVISIONS;
+
+/// Overall graphics quality. Changing it resets every setting derived from it.
+#[derive(Resource, Default, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum QualityPreset {
+    Low,
+    #[default]
+    Medium,
+    High,
+    Ultra,
+}
+
+impl QualityPreset {
+    pub fn next(self) -> Self {
+        match self {
+            QualityPreset::Low => QualityPreset::Medium,
+            QualityPreset::Medium => QualityPreset::High,
+            QualityPreset::High => QualityPreset::Ultra,
+            QualityPreset::Ultra => QualityPreset::Low,
+        }
+    }
+
+    pub fn asteroid_lod(self) -> AsteroidLodSettings {
+        let (lod_distances, hysteresis) = match self {
+            QualityPreset::Low => ([15.0, 50.0, 120.0], 0.15),
+            QualityPreset::Medium => ([30.0, 100.0, 250.0], 0.1),
+            QualityPreset::High => ([50.0, 160.0, 400.0], 0.1),
+            QualityPreset::Ultra => ([80.0, 250.0, 700.0], 0.05),
+        };
+        AsteroidLodSettings {
+            lod_distances,
+            hysteresis,
+        }
+    }
+}
+
+/// When asteroids switch between their level of detail meshes and the impostor.
+#[derive(Resource, Debug, Clone, PartialEq)]
+pub struct AsteroidLodSettings {
+    /// Camera distance, divided by the asteroid's largest scale, beyond which each level of detail
+    /// gives way to the next. Past the last one asteroids are drawn as impostors.
+    pub lod_distances: [f32; LOD_SUBDIVISIONS.len()],
+    /// Fraction of a switching distance an asteroid has to move past it before it switches, so
+    /// asteroids near the boundary don't flicker between levels.
+    pub hysteresis: f32,
+}
+
+impl Default for AsteroidLodSettings {
+    fn default() -> Self {
+        QualityPreset::default().asteroid_lod()
+    }
+}
+
+impl AsteroidLodSettings {
+    /// The level an asteroid at `level` should switch to at the scaled `distance`, where
+    /// `LOD_SUBDIVISIONS.len()` is the impostor.
+    pub fn level(&self, level: usize, distance: f32) -> usize {
+        let mut level = level.min(self.lod_distances.len());
+        while level < self.lod_distances.len()
+            && distance > self.lod_distances[level] * (1.0 + self.hysteresis)
+        {
+            level += 1;
+        }
+        while level > 0 && distance < self.lod_distances[level - 1] * (1.0 - self.hysteresis) {
+            level -= 1;
+        }
+        level
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SETTINGS: AsteroidLodSettings = AsteroidLodSettings {
+        lod_distances: [30.0, 100.0, 250.0],
+        hysteresis: 0.1,
+    };
+    const IMPOSTOR: usize = LOD_SUBDIVISIONS.len();
+
+    #[test]
+    fn levels_hold_inside_the_hysteresis_band() {
+        // The band around the 100 boundary is 90 to 110.
+        for distance in [91.0, 100.0, 109.0] {
+            assert_eq!(SETTINGS.level(1, distance), 1);
+            assert_eq!(SETTINGS.level(2, distance), 2);
+        }
+        assert_eq!(SETTINGS.level(1, 111.0), 2);
+        assert_eq!(SETTINGS.level(2, 89.0), 1);
+    }
+
+    #[test]
+    fn jumps_land_on_the_right_level() {
+        assert_eq!(SETTINGS.level(0, 1000.0), IMPOSTOR);
+        assert_eq!(SETTINGS.level(0, 200.0), 2);
+        assert_eq!(SETTINGS.level(IMPOSTOR, 0.0), 0);
+        assert_eq!(SETTINGS.level(IMPOSTOR, 50.0), 1);
+        // Out of range levels are treated as the impostor.
+        assert_eq!(SETTINGS.level(IMPOSTOR + 3, 50.0), 1);
+    }
+}
@@ -41,6 +41,6 @@ impl Gauge {
             info!("max value is 0");
             return 0.0;
         }
-        return self.current / self.max;
+        self.current / self.max
     }
 }
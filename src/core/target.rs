@@ -0,0 +1,7 @@
+use bevy::prelude::*;
+
+#[derive(Component)]
+pub struct Targetable;
+
+#[derive(Component)]
+pub struct LockedTarget(pub Entity);
@@ -0,0 +1,8 @@
+use bevy::prelude::*;
+
+/// Sent when a weapon fires, with the hardpoint the shot leaves from.
+#[derive(Message)]
+pub struct WeaponFired {
+    pub shooter: Entity,
+    pub hardpoint: Transform,
+}
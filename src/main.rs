@@ -1,17 +1,20 @@
 mod core;
+mod noise;
 mod plugins;
 mod resources;
 
 use avian3d::{prelude::Gravity, PhysicsPlugins};
 use bevy::{dev_tools::fps_overlay::FpsOverlayPlugin, prelude::*, window::WindowResolution};
 use plugins::{
-    asteroid::AsteroidPlugin, chromatic_abberation::ChromaticAbberationPlugin,
-    main_camera::MainCameraPlugin, player::PlayerPlugin, player_controller::PlayerControllerPlugin,
-    procedural_skybox::ProceduralSkyboxPlugin, projectile::ProjectilePlugin,
-    scene_lighting::SceneLightingPlugin, upgrade::UpgradePlugin,
-    volumetric_nebula::VolumetricNebulaPlugin, weapon::WeaponPlugin,
+    asteroid::AsteroidPlugin, asteroid_lod::AsteroidLodPlugin,
+    chromatic_abberation::ChromaticAbberationPlugin, damage::DamagePlugin,
+    main_camera::MainCameraPlugin, nebula_effects::NebulaEffectsPlugin, outline::OutlinePlugin,
+    particles::ParticlesPlugin, player::PlayerPlugin, player_controller::PlayerControllerPlugin,
+    post_process::PostProcessChainPlugin, procedural_skybox::ProceduralSkyboxPlugin,
+    projectile::ProjectilePlugin, scene_lighting::SceneLightingPlugin, targeting::TargetingPlugin,
+    upgrade::UpgradePlugin, volumetric_nebula::VolumetricNebulaPlugin, weapon::WeaponPlugin,
 };
-use resources::upgrades::Upgrades;
+use resources::{upgrades::Upgrades, world_seed::WorldSeed};
 
 fn main() {
     App::new()
@@ -23,18 +26,28 @@ fn main() {
             }),
             ..default()
         }))
+        // After `DefaultPlugins`, which sets up logging, so the seed is logged. Before the other
+        // plugins, whose resources derive their randomness from it.
+        .insert_resource(WorldSeed::from_env())
         .add_plugins(PhysicsPlugins::default())
         .add_plugins(MainCameraPlugin)
         .add_plugins(PlayerPlugin)
         .add_plugins(FpsOverlayPlugin::default())
         .add_plugins(ProceduralSkyboxPlugin)
         .add_plugins(VolumetricNebulaPlugin)
+        .add_plugins(NebulaEffectsPlugin)
         .add_plugins(ChromaticAbberationPlugin)
+        .add_plugins(OutlinePlugin)
+        .add_plugins(PostProcessChainPlugin)
         .add_plugins(PlayerControllerPlugin)
         .add_plugins(AsteroidPlugin)
+        .add_plugins(AsteroidLodPlugin)
+        .add_plugins(ParticlesPlugin)
         .add_plugins(UpgradePlugin)
         .add_plugins(WeaponPlugin)
         .add_plugins(ProjectilePlugin)
+        .add_plugins(DamagePlugin)
+        .add_plugins(TargetingPlugin)
         .add_plugins(SceneLightingPlugin)
         .insert_resource(Upgrades::default())
         .insert_resource(Gravity::ZERO)
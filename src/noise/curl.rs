@@ -0,0 +1,23 @@
+use bevy::math::Vec3;
+
+use crate::noise::simplex::simplex_3d;
+
+fn curl_potential(p: Vec3) -> Vec3 {
+    Vec3::new(
+        simplex_3d(p),
+        simplex_3d(p + Vec3::new(31.4, 15.9, 26.5)),
+        simplex_3d(p + Vec3::new(-35.8, 97.9, -32.3)),
+    )
+}
+
+/// Divergence free flow field, the curl of a simplex noise vector potential.
+pub fn curl_3d(p: Vec3) -> Vec3 {
+    let e = 0.01;
+    let dx =
+        curl_potential(p + Vec3::new(e, 0.0, 0.0)) - curl_potential(p - Vec3::new(e, 0.0, 0.0));
+    let dy =
+        curl_potential(p + Vec3::new(0.0, e, 0.0)) - curl_potential(p - Vec3::new(0.0, e, 0.0));
+    let dz =
+        curl_potential(p + Vec3::new(0.0, 0.0, e)) - curl_potential(p - Vec3::new(0.0, 0.0, e));
+    Vec3::new(dy.z - dz.y, dz.x - dx.z, dx.y - dy.x) / (2.0 * e)
+}
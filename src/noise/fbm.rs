@@ -0,0 +1,34 @@
+use bevy::math::Vec3;
+
+use crate::noise::simplex::simplex_3d;
+
+/// Sum of `octaves` layers of simplex noise, normalized back to roughly [-1, 1].
+pub fn fbm_3d(p: Vec3, octaves: u32, lacunarity: f32, gain: f32) -> f32 {
+    let mut sum = 0.0;
+    let mut amplitude = 1.0;
+    let mut frequency = 1.0;
+    let mut total = 0.0;
+    for _ in 0..octaves {
+        sum += amplitude * simplex_3d(p * frequency);
+        total += amplitude;
+        amplitude *= gain;
+        frequency *= lacunarity;
+    }
+    sum / f32::max(total, 1e-6)
+}
+
+/// Moves `p` along three decorrelated fbm fields.
+#[cfg(test)]
+pub fn domain_warp_3d(p: Vec3, strength: f32) -> Vec3 {
+    let offset = Vec3::new(
+        fbm_3d(p, 4, 2.0, 0.5),
+        fbm_3d(p + Vec3::new(5.2, 1.3, 2.8), 4, 2.0, 0.5),
+        fbm_3d(p + Vec3::new(1.7, 9.2, 3.4), 4, 2.0, 0.5),
+    );
+    p + strength * offset
+}
+
+#[cfg(test)]
+pub fn warped_fbm_3d(p: Vec3, strength: f32, octaves: u32, lacunarity: f32, gain: f32) -> f32 {
+    fbm_3d(domain_warp_3d(p, strength), octaves, lacunarity, gain)
+}
@@ -0,0 +1,46 @@
+use bevy::math::{UVec3, Vec3};
+
+/// PCG3D, matching `pcg_3d` in `hash.wgsl` exactly.
+pub fn pcg_3d(v: UVec3) -> UVec3 {
+    let mut v = v
+        .wrapping_mul(UVec3::splat(1664525))
+        .wrapping_add(UVec3::splat(1013904223));
+    v.x = v.x.wrapping_add(v.y.wrapping_mul(v.z));
+    v.y = v.y.wrapping_add(v.z.wrapping_mul(v.x));
+    v.z = v.z.wrapping_add(v.x.wrapping_mul(v.y));
+    v ^= v >> 16;
+    v.x = v.x.wrapping_add(v.y.wrapping_mul(v.z));
+    v.y = v.y.wrapping_add(v.z.wrapping_mul(v.x));
+    v.z = v.z.wrapping_add(v.x.wrapping_mul(v.y));
+    v
+}
+
+/// Three values in [0, 1) for an integer lattice cell.
+pub fn cell_hash_33(cell: Vec3) -> Vec3 {
+    let h = pcg_3d(cell.as_ivec3().as_uvec3());
+    (h >> 8u32).as_vec3() * (1.0 / 16777216.0)
+}
+
+/// Three values in [0, 1) for a point, from its exact bits.
+#[cfg(test)]
+pub fn hash_33(p: Vec3) -> Vec3 {
+    let h = pcg_3d(UVec3::new(p.x.to_bits(), p.y.to_bits(), p.z.to_bits()));
+    (h >> 8u32).as_vec3() * (1.0 / 16777216.0)
+}
+
+#[cfg(test)]
+pub fn hash_31(p: Vec3) -> f32 {
+    hash_33(p).x
+}
+
+#[cfg(test)]
+pub fn hash_21(p: bevy::math::Vec2) -> f32 {
+    hash_33(p.extend(0.0)).x
+}
+
+/// Three values in [0, 1) for an integer id, such as a `MeshTag`.
+#[cfg(test)]
+pub fn uint_hash_13(x: u32) -> Vec3 {
+    let h = pcg_3d(UVec3::new(x, x ^ 0x5bd1e995, 0x27d4eb2f));
+    (h >> 8u32).as_vec3() * (1.0 / 16777216.0)
+}
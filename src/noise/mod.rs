@@ -0,0 +1,19 @@
+// CPU ports of the WGSL noise functions in `assets/shaders/noise`, so gameplay code can sample
+// the same fields the shaders draw. Keep both sides in sync when changing either; the tests
+// compare them against outputs of the WGSL. Ports without a gameplay caller yet are only built
+// for the tests.
+
+#[cfg(test)]
+pub mod curl;
+pub mod fbm;
+pub mod hash;
+pub mod nebula;
+#[cfg(test)]
+pub mod perlin;
+pub mod simplex;
+pub mod value;
+#[cfg(test)]
+pub mod worley;
+
+#[cfg(test)]
+mod tests;
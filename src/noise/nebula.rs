@@ -0,0 +1,14 @@
+use bevy::math::{Vec3, Vec4};
+
+use super::{hash::cell_hash_33, value::value_3d};
+
+const NEBULA_GRAIN_FREQUENCY: f32 = 16.0;
+
+/// Matches `nebula_noise` in `nebula.wgsl` exactly, since its grain layer uses the integer hash.
+pub fn nebula_noise(point: Vec3) -> Vec4 {
+    let n1 = value_3d(point * 0.2 * Vec3::new(0.75, 1.25, 0.75)) * Vec4::new(0.8, 0.2, 1.0, 1.0);
+    let n2 = value_3d(point * 0.7 * Vec3::new(1.25, 0.75, 1.25)) * Vec4::new(0.1, 1.0, 0.2, 1.0);
+    let n4 =
+        cell_hash_33((point * NEBULA_GRAIN_FREQUENCY).floor()).x * Vec4::new(1.0, 1.0, 0.8, 1.0);
+    (n1 * 28.0 + n2 * 18.0 + n4 * 4.0) / 50.0
+}
@@ -0,0 +1,26 @@
+use bevy::math::{FloatExt, Vec3};
+
+use crate::noise::hash::cell_hash_33;
+
+fn perlin_gradient(cell: Vec3, offset: Vec3) -> f32 {
+    (cell_hash_33(cell) * 2.0 - 1.0).dot(offset)
+}
+
+/// 3D gradient noise with quintic fade, roughly in [-1, 1].
+pub fn perlin_3d(p: Vec3) -> f32 {
+    let i = p.floor();
+    let f = p - i;
+    let u = f * f * f * (f * (f * 6.0 - 15.0) + 10.0);
+
+    let corner = |x: f32, y: f32, z: f32| {
+        let corner = Vec3::new(x, y, z);
+        perlin_gradient(i + corner, f - corner)
+    };
+    let x00 = corner(0.0, 0.0, 0.0).lerp(corner(1.0, 0.0, 0.0), u.x);
+    let x10 = corner(0.0, 1.0, 0.0).lerp(corner(1.0, 1.0, 0.0), u.x);
+    let x01 = corner(0.0, 0.0, 1.0).lerp(corner(1.0, 0.0, 1.0), u.x);
+    let x11 = corner(0.0, 1.0, 1.0).lerp(corner(1.0, 1.0, 1.0), u.x);
+    let y0 = x00.lerp(x10, u.y);
+    let y1 = x01.lerp(x11, u.y);
+    y0.lerp(y1, u.z)
+}
@@ -0,0 +1,38 @@
+use bevy::math::{Vec3, Vec3Swizzles};
+
+use crate::noise::hash::cell_hash_33;
+
+fn simplex_corner(cell: Vec3, offset: Vec3) -> f32 {
+    let t = (0.6 - offset.dot(offset)).max(0.0);
+    let t2 = t * t;
+    t2 * t2 * (cell_hash_33(cell) * 2.0 - 1.0).dot(offset)
+}
+
+// WGSL `step(edge, x)`.
+fn step(edge: Vec3, x: Vec3) -> Vec3 {
+    Vec3::select(x.cmpge(edge), Vec3::ONE, Vec3::ZERO)
+}
+
+/// 3D simplex noise (Gustavson), roughly in [-1, 1].
+pub fn simplex_3d(p: Vec3) -> f32 {
+    let skew = 1.0 / 3.0;
+    let unskew = 1.0 / 6.0;
+
+    let i = (p + (p.x + p.y + p.z) * skew).floor();
+    let x0 = p - i + (i.x + i.y + i.z) * unskew;
+
+    // Order the corners by which axes the point is furthest along.
+    let g = step(x0.yzx(), x0);
+    let l = 1.0 - g;
+    let i1 = g.min(l.zxy());
+    let i2 = g.max(l.zxy());
+
+    let x1 = x0 - i1 + unskew;
+    let x2 = x0 - i2 + 2.0 * unskew;
+    let x3 = x0 - 1.0 + 3.0 * unskew;
+
+    32.0 * (simplex_corner(i, x0)
+        + simplex_corner(i + i1, x1)
+        + simplex_corner(i + i2, x2)
+        + simplex_corner(i + 1.0, x3))
+}
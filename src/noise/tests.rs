@@ -0,0 +1,323 @@
+use bevy::{
+    math::{Vec3, Vec4},
+    render::{
+        render_resource::{
+            BindGroupEntries, BufferDescriptor, BufferInitDescriptor, BufferUsages,
+            CommandEncoderDescriptor, ComputePassDescriptor, MapMode, PollType,
+            RawComputePipelineDescriptor, ShaderModuleDescriptor, ShaderSource,
+        },
+        renderer::initialize_renderer,
+        settings::{Backends, RenderResources, WgpuSettings},
+    },
+    tasks::block_on,
+};
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+use crate::noise::{
+    curl::curl_3d,
+    fbm::{fbm_3d, warped_fbm_3d},
+    hash::{cell_hash_33, hash_21, hash_31, hash_33, uint_hash_13},
+    nebula::nebula_noise,
+    perlin::perlin_3d,
+    simplex::simplex_3d,
+    value::value_3d,
+    worley::worley_3d,
+};
+
+const NOISE_SOURCES: [&str; 8] = [
+    include_str!("../../assets/shaders/noise/hash.wgsl"),
+    include_str!("../../assets/shaders/noise/value.wgsl"),
+    include_str!("../../assets/shaders/noise/perlin.wgsl"),
+    include_str!("../../assets/shaders/noise/simplex.wgsl"),
+    include_str!("../../assets/shaders/noise/fbm.wgsl"),
+    include_str!("../../assets/shaders/noise/curl.wgsl"),
+    include_str!("../../assets/shaders/noise/worley.wgsl"),
+    include_str!("../../assets/shaders/noise/nebula.wgsl"),
+];
+
+// Evaluates every noise function at each point and writes `RESULTS_PER_POINT` vectors, laid out
+// like `cpu_results`.
+const ENTRY_POINT: &str = r"
+@group(0) @binding(0) var<storage, read> points: array<vec4<f32>>;
+@group(0) @binding(1) var<storage, read_write> results: array<vec4<f32>>;
+
+@compute @workgroup_size(64)
+fn main(@builtin(global_invocation_id) id: vec3<u32>) {
+    let i = id.x;
+    if i >= arrayLength(&points) {
+        return;
+    }
+    let p = points[i].xyz;
+    results[i * 7u] = vec4<f32>(value_3d(p), perlin_3d(p), simplex_3d(p), fbm_3d(p, 5u, 2.0, 0.5));
+    results[i * 7u + 1u] = vec4<f32>(worley_3d(p), warped_fbm_3d(p, 0.5, 3u, 2.0, 0.5), 0.0);
+    results[i * 7u + 2u] = vec4<f32>(curl_3d(p), 0.0);
+    results[i * 7u + 3u] = vec4<f32>(cell_hash_33(floor(p)), 0.0);
+    results[i * 7u + 4u] = vec4<f32>(uint_hash_13(bitcast<u32>(p.x)), hash_21(p.xy));
+    results[i * 7u + 5u] = nebula_noise(p);
+    results[i * 7u + 6u] = vec4<f32>(hash_33(p), hash_31(p));
+}
+";
+const RESULTS_PER_POINT: usize = 7;
+// How far each result may be from the WGSL. The hashes are integer only and match exactly. Curl
+// takes differences over a small step, which scales up the rounding differences of the noise.
+const TOLERANCES: [f32; RESULTS_PER_POINT] = [1e-3, 1e-3, 5e-3, 0.0, 0.0, 1e-3, 0.0];
+
+// `ENTRY_POINT`'s results at each point, captured on a software adapter (llvmpipe), so the CPU
+// ports are checked against the WGSL without a GPU.
+const GOLDEN_POINTS: [Vec3; 6] = [
+    Vec3::new(0.25, 0.5, 0.75),
+    Vec3::new(-1.5, 2.25, -3.125),
+    Vec3::new(12.3, -45.6, 78.9),
+    Vec3::new(-99.5, 0.1, 33.3),
+    Vec3::new(3.0, -7.0, 11.0),
+    Vec3::new(0.9, -0.9, 64.2),
+];
+const WGSL_GOLDENS: [[Vec4; RESULTS_PER_POINT]; 6] = [
+    [
+        Vec4::new(0.4736565, 0.09671077, -0.41841087, -0.2888329),
+        Vec4::new(0.548199, 0.6382809, -0.12930301, 0.0),
+        Vec4::new(0.5965054, -1.8972218, -0.34076944, 0.0),
+        Vec4::new(0.60815185, 0.65979826, 0.24641848, 0.0),
+        Vec4::new(0.86035454, 0.23996449, 0.46577024, 0.37417394),
+        Vec4::new(0.10443367, 0.25595826, 0.11831186, 0.28243402),
+        Vec4::new(0.6971779, 0.35780114, 0.65724266, 0.6971779),
+    ],
+    [
+        Vec4::new(0.76662964, 0.13323472, 0.043184407, -0.07786938),
+        Vec4::new(0.5756092, 0.62355506, 0.021678016, 0.0),
+        Vec4::new(2.7307465, 0.049019977, 0.967747, 0.0),
+        Vec4::new(0.17642146, 0.78204817, 0.907289, 0.0),
+        Vec4::new(0.24024743, 0.4692933, 0.43925327, 0.78745896),
+        Vec4::new(0.32814, 0.20034079, 0.39538795, 0.47291505),
+        Vec4::new(0.021654844, 0.04193616, 0.23519385, 0.021654844),
+    ],
+    [
+        Vec4::new(0.3624337, -0.025965154, -0.0014236532, 0.014611919),
+        Vec4::new(0.6295544, 0.8694179, -0.006088063, 0.0),
+        Vec4::new(-1.0442613, 0.87925094, 0.1375433, 0.0),
+        Vec4::new(0.7874801, 0.071962, 0.03871417, 0.0),
+        Vec4::new(0.071284115, 0.2818491, 0.0503363, 0.29759437),
+        Vec4::new(0.25258467, 0.36908895, 0.3011124, 0.52092665),
+        Vec4::new(0.4004467, 0.8037699, 0.45414752, 0.4004467),
+    ],
+    [
+        Vec4::new(0.63773817, 0.38056245, -0.32356313, -0.20141317),
+        Vec4::new(0.79336363, 0.8088595, -0.2870047, 0.0),
+        Vec4::new(2.575422, 1.1661321, -0.6081399, 0.0),
+        Vec4::new(0.9859904, 0.9106583, 0.768151, 0.0),
+        Vec4::new(0.28477854, 0.9427792, 0.33639395, 0.75791705),
+        Vec4::new(0.34859374, 0.2906771, 0.44966707, 0.61524856),
+        Vec4::new(0.3096494, 0.536902, 0.47122264, 0.3096494),
+    ],
+    [
+        Vec4::new(0.14634132, 0.0, 0.27729318, -0.10717028),
+        Vec4::new(0.59347385, 0.97929734, -0.08323126, 0.0),
+        Vec4::new(-0.40848777, -0.698372, -1.1704541, 0.0),
+        Vec4::new(0.3139642, 0.93469495, 0.71415776, 0.0),
+        Vec4::new(0.8818705, 0.50676036, 0.24799639, 0.8488087),
+        Vec4::new(0.24633637, 0.17863344, 0.2897572, 0.3635701),
+        Vec4::new(0.11022365, 0.5685681, 0.07626861, 0.11022365),
+    ],
+    [
+        Vec4::new(0.59945685, -0.09344691, 0.49203914, 0.28434402),
+        Vec4::new(0.27118325, 0.312989, 0.4682631, 0.0),
+        Vec4::new(1.6035602, -0.21928549, 1.008442, 0.0),
+        Vec4::new(0.6005415, 0.09141469, 0.2906226, 0.0),
+        Vec4::new(0.9588748, 0.88214016, 0.13517666, 0.11958665),
+        Vec4::new(0.42229405, 0.4125411, 0.5152241, 0.7374306),
+        Vec4::new(0.8004945, 0.18897825, 0.80157244, 0.8004945),
+    ],
+];
+
+fn cpu_results(p: Vec3) -> [Vec4; RESULTS_PER_POINT] {
+    [
+        Vec4::new(
+            value_3d(p),
+            perlin_3d(p),
+            simplex_3d(p),
+            fbm_3d(p, 5, 2.0, 0.5),
+        ),
+        worley_3d(p)
+            .extend(warped_fbm_3d(p, 0.5, 3, 2.0, 0.5))
+            .extend(0.0),
+        curl_3d(p).extend(0.0),
+        cell_hash_33(p.floor()).extend(0.0),
+        uint_hash_13(p.x.to_bits()).extend(hash_21(p.truncate())),
+        nebula_noise(p),
+        hash_33(p).extend(hash_31(p)),
+    ]
+}
+
+fn sample_points() -> Vec<Vec3> {
+    let mut rng = StdRng::seed_from_u64(1);
+    (0..1024)
+        .map(|_| {
+            Vec3::new(
+                rng.gen_range(-100.0..100.0),
+                rng.gen_range(-100.0..100.0),
+                rng.gen_range(-100.0..100.0),
+            )
+        })
+        .collect()
+}
+
+// The noise files joined into one module, since the test compiles them without Bevy's shader
+// preprocessor.
+fn shader_source() -> String {
+    NOISE_SOURCES
+        .iter()
+        .flat_map(|source| source.lines())
+        .filter(|line| !line.starts_with("#import"))
+        .chain(ENTRY_POINT.lines())
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Runs the WGSL noise on the GPU.
+fn gpu_results(points: &[Vec3]) -> Vec<Vec4> {
+    // The default settings honor `WGPU_BACKEND`, so a software adapter can be picked on machines
+    // without a GPU.
+    let settings = WgpuSettings::default();
+    let backends = settings.backends.unwrap_or(Backends::all());
+    let RenderResources(device, queue, ..) =
+        block_on(initialize_renderer(backends, None, &settings));
+
+    let module = device.create_and_validate_shader_module(ShaderModuleDescriptor {
+        label: Some("noise_parity_shader"),
+        source: ShaderSource::Wgsl(shader_source().into()),
+    });
+    let pipeline = device.create_compute_pipeline(&RawComputePipelineDescriptor {
+        label: Some("noise_parity_pipeline"),
+        layout: None,
+        module: &module,
+        entry_point: Some("main"),
+        compilation_options: Default::default(),
+        cache: None,
+    });
+
+    let point_bytes: Vec<u8> = points
+        .iter()
+        .flat_map(|point| point.extend(0.0).to_array())
+        .flat_map(f32::to_le_bytes)
+        .collect();
+    let points_buffer = device.create_buffer_with_data(&BufferInitDescriptor {
+        label: Some("noise_parity_points"),
+        contents: &point_bytes,
+        usage: BufferUsages::STORAGE,
+    });
+    let results_size = (points.len() * RESULTS_PER_POINT * size_of::<Vec4>()) as u64;
+    let results_buffer = device.create_buffer(&BufferDescriptor {
+        label: Some("noise_parity_results"),
+        size: results_size,
+        usage: BufferUsages::STORAGE | BufferUsages::COPY_SRC,
+        mapped_at_creation: false,
+    });
+    let readback_buffer = device.create_buffer(&BufferDescriptor {
+        label: Some("noise_parity_readback"),
+        size: results_size,
+        usage: BufferUsages::MAP_READ | BufferUsages::COPY_DST,
+        mapped_at_creation: false,
+    });
+    let bind_group = device.create_bind_group(
+        "noise_parity_bind_group",
+        &pipeline.get_bind_group_layout(0).into(),
+        &BindGroupEntries::sequential((
+            points_buffer.as_entire_binding(),
+            results_buffer.as_entire_binding(),
+        )),
+    );
+
+    let mut encoder = device.create_command_encoder(&CommandEncoderDescriptor::default());
+    {
+        let mut pass = encoder.begin_compute_pass(&ComputePassDescriptor::default());
+        pass.set_pipeline(&pipeline);
+        pass.set_bind_group(0, &*bind_group, &[]);
+        pass.dispatch_workgroups((points.len() as u32).div_ceil(64), 1, 1);
+    }
+    encoder.copy_buffer_to_buffer(&results_buffer, 0, &readback_buffer, 0, results_size);
+    queue.submit([encoder.finish()]);
+
+    let slice = readback_buffer.slice(..);
+    slice.map_async(MapMode::Read, |result| result.unwrap());
+    device.poll(PollType::Wait).unwrap();
+    let bytes = slice.get_mapped_range();
+    bytes
+        .chunks_exact(size_of::<Vec4>())
+        .map(|chunk| {
+            Vec4::from_array(std::array::from_fn(|i| {
+                f32::from_le_bytes(chunk[i * 4..i * 4 + 4].try_into().unwrap())
+            }))
+        })
+        .collect()
+}
+
+#[test]
+#[ignore = "needs a GPU or software adapter, run with `cargo test -- --ignored`"]
+fn cpu_matches_wgsl() {
+    let points = sample_points();
+    let gpu = gpu_results(&points);
+    for (point, gpu) in points.iter().zip(gpu.chunks_exact(RESULTS_PER_POINT)) {
+        for (index, (cpu, gpu)) in cpu_results(*point).iter().zip(gpu).enumerate() {
+            assert!(
+                (*cpu - *gpu).abs().max_element() <= TOLERANCES[index],
+                "result {index} at {point}: cpu {cpu}, gpu {gpu}"
+            );
+        }
+    }
+}
+
+#[test]
+fn cpu_matches_wgsl_goldens() {
+    for (point, goldens) in GOLDEN_POINTS.iter().zip(WGSL_GOLDENS) {
+        for (index, (cpu, golden)) in cpu_results(*point).iter().zip(goldens).enumerate() {
+            assert!(
+                (*cpu - golden).abs().max_element() <= TOLERANCES[index],
+                "result {index} at {point}: cpu {cpu}, wgsl {golden}"
+            );
+        }
+    }
+}
+
+#[test]
+fn worley_distances_are_ordered() {
+    for point in sample_points() {
+        let distances = worley_3d(point);
+        assert!(distances.x <= distances.y, "{distances} at {point}");
+        // Every point is within one cell diagonal of its own cell's feature point.
+        assert!(distances.x <= 3.0_f32.sqrt(), "{distances} at {point}");
+    }
+}
+
+#[test]
+fn gradient_noise_stays_in_range() {
+    for point in sample_points() {
+        for value in [
+            perlin_3d(point),
+            simplex_3d(point),
+            fbm_3d(point, 5, 2.0, 0.5),
+        ] {
+            assert!(value.abs() <= 1.5, "{value} at {point}");
+        }
+    }
+}
+
+#[test]
+fn curl_is_divergence_free() {
+    // The same step as `curl_3d`, so the finite differences commute and the divergence cancels up
+    // to rounding. Points are scaled towards the origin to keep that rounding small.
+    let e = 0.01;
+    for point in sample_points()
+        .into_iter()
+        .take(64)
+        .map(|point| point * 0.05)
+    {
+        let divergence = (curl_3d(point + Vec3::X * e).x - curl_3d(point - Vec3::X * e).x
+            + curl_3d(point + Vec3::Y * e).y
+            - curl_3d(point - Vec3::Y * e).y
+            + curl_3d(point + Vec3::Z * e).z
+            - curl_3d(point - Vec3::Z * e).z)
+            / (2.0 * e);
+        let scale = curl_3d(point).length().max(1.0);
+        assert!(divergence.abs() <= 0.1 * scale, "{divergence} at {point}");
+    }
+}
@@ -0,0 +1,38 @@
+use bevy::math::{Vec2, Vec3, Vec4};
+
+// 3D Value Noise
+// MIT License. © Stefan Gustavson, Munrocket
+
+fn mod289(x: Vec4) -> Vec4 {
+    x - (x * (1. / 289.)).floor() * 289.
+}
+
+fn perm4(x: Vec4) -> Vec4 {
+    mod289(((x * 34.) + 1.) * x)
+}
+
+fn fract4(x: Vec4) -> Vec4 {
+    x - x.floor()
+}
+
+pub fn value_3d(p: Vec3) -> f32 {
+    let a = p.floor();
+    let mut d = p - a;
+    d = d * d * (3. - 2. * d);
+
+    let b = Vec4::new(a.x, a.x, a.y, a.y) + Vec4::new(0., 1., 0., 1.);
+    let k1 = perm4(Vec4::new(b.x, b.y, b.x, b.y));
+    let k2 = perm4(Vec4::new(k1.x, k1.y, k1.x, k1.y) + Vec4::new(b.z, b.z, b.w, b.w));
+
+    let c = k2 + a.z;
+    let k3 = perm4(c);
+    let k4 = perm4(c + 1.);
+
+    let o1 = fract4(k3 * (1. / 41.));
+    let o2 = fract4(k4 * (1. / 41.));
+
+    let o3 = o2 * d.z + o1 * (1. - d.z);
+    let o4 = Vec2::new(o3.y, o3.w) * d.x + Vec2::new(o3.x, o3.z) * (1. - d.x);
+
+    o4.y * d.y + o4.x * (1. - d.y)
+}
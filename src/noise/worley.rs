@@ -0,0 +1,28 @@
+use bevy::math::{Vec2, Vec3};
+
+use crate::noise::hash::cell_hash_33;
+
+/// Distances to the nearest (F1) and second nearest (F2) feature points, one point per cell.
+pub fn worley_3d(p: Vec3) -> Vec2 {
+    let cell = p.floor();
+    let local = p - cell;
+
+    let mut f1 = 8.0;
+    let mut f2 = 8.0;
+    for z in -1..=1 {
+        for y in -1..=1 {
+            for x in -1..=1 {
+                let offset = Vec3::new(x as f32, y as f32, z as f32);
+                let r = offset + cell_hash_33(cell + offset) - local;
+                let d = r.dot(r);
+                if d < f1 {
+                    f2 = f1;
+                    f1 = d;
+                } else if d < f2 {
+                    f2 = d;
+                }
+            }
+        }
+    }
+    Vec2::new(f1, f2).map(f32::sqrt)
+}
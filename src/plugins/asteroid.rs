@@ -1,73 +1,186 @@
-use bevy::prelude::*;
-use rand::Rng;
+use avian3d::prelude::RigidBody;
+use bevy::{mesh::MeshTag, platform::collections::HashSet, prelude::*};
+
+use crate::{
+    core::{
+        asteroid::{AsteroidId, AsteroidLod, AsteroidMaterial, Cargo, OreTier},
+        asteroid_field::FieldLayout,
+        player::Player,
+        stats::{Gauge, Health},
+        target::Targetable,
+    },
+    plugins::{
+        damage::{apply_damage, destroy_depleted},
+        ron_asset::RonAssetPlugin,
+    },
+    resources::{
+        asteroid_field::{AsteroidChunks, AsteroidField},
+        asteroid_materials::AsteroidMaterials,
+        asteroid_meshes::{AsteroidMeshes, AsteroidVariant},
+    },
+};
 
 pub struct AsteroidPlugin;
 impl Plugin for AsteroidPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(Startup, spawn_asteroids);
+        app.add_plugins(MaterialPlugin::<AsteroidMaterial>::default())
+            .add_plugins(RonAssetPlugin::<FieldLayout>::new(&["fields.ron"]))
+            .init_resource::<AsteroidMaterials>()
+            .init_resource::<AsteroidMeshes>()
+            .init_resource::<AsteroidField>()
+            .init_resource::<AsteroidChunks>()
+            .add_systems(Update, stream_asteroid_chunks)
+            .add_systems(
+                Update,
+                (record_destroyed_asteroids, collect_ore)
+                    .after(apply_damage)
+                    .before(destroy_depleted),
+            );
     }
 }
 
 #[derive(Component)]
 pub struct Asteroid;
 
-pub fn spawn_asteroids(
+#[allow(clippy::too_many_arguments)]
+fn stream_asteroid_chunks(
     mut commands: Commands,
-    asset_server: Res<AssetServer>,
-    mut materials: ResMut<Assets<StandardMaterial>>,
+    field: Res<AsteroidField>,
+    layouts: Res<Assets<FieldLayout>>,
+    mut chunks: ResMut<AsteroidChunks>,
+    mut asteroid_meshes: ResMut<AsteroidMeshes>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    asteroid_materials: Res<AsteroidMaterials>,
+    player_query: Query<&Transform, With<Player>>,
 ) {
-    let mut asteroid_meshes: Vec<Handle<Mesh>> = Vec::new();
-    let mesh_variants = 3;
-    let asteroid_count = 1000;
-    for variant in 0..mesh_variants {
-        asteroid_meshes.push(
-            asset_server.load(
-                GltfAssetLabel::Primitive {
-                    mesh: variant,
-                    primitive: 0,
-                }
-                .from_asset("meshes/asteroid.glb"),
-            ),
-        );
-    }
-    let asteroid_material_handle = materials.add(StandardMaterial {
-        base_color: Color::srgb(0.25, 0.2, 0.15),
-        ..default()
+    let (Ok(player_transform), Some(layout)) = (player_query.single(), layouts.get(&field.layout))
+    else {
+        return;
+    };
+    let center = field.chunk_coord(player_transform.translation);
+
+    let unload_radius_squared = field.unload_radius.pow(2);
+    chunks.loaded.retain(|coord, entities| {
+        let keep = (*coord - center).length_squared() <= unload_radius_squared;
+        if !keep {
+            for entity in entities.iter() {
+                commands.entity(*entity).try_despawn();
+            }
+            asteroid_meshes.unload_chunk(*coord);
+        }
+        keep
     });
-    for _ in 0..asteroid_count {
-        commands.spawn((
-            Asteroid,
-            Mesh3d(asteroid_meshes[rand::thread_rng().gen_range(0..mesh_variants)].clone()),
-            MeshMaterial3d(asteroid_material_handle.clone()),
-            Transform {
-                translation: random_vec3_in_sphere(),
-                scale: random_vec3(0.75, 1.25),
-                ..default()
-            },
-        ));
+
+    // Nearest chunks first, so the player never flies into an empty one.
+    let radius = field.load_radius;
+    let mut missing: Vec<IVec3> = (-radius..=radius)
+        .flat_map(|x| {
+            (-radius..=radius)
+                .flat_map(move |y| (-radius..=radius).map(move |z| IVec3::new(x, y, z)))
+        })
+        .filter(|offset| offset.length_squared() <= radius.pow(2))
+        .map(|offset| center + offset)
+        .filter(|coord| !chunks.loaded.contains_key(coord))
+        .collect();
+    missing.sort_by_key(|coord| (*coord - center).length_squared());
+    for coord in missing.into_iter().take(field.max_chunk_loads_per_frame) {
+        let entities = spawn_chunk(
+            &mut commands,
+            &field,
+            layout,
+            asteroid_meshes.load_chunk(&mut meshes, coord),
+            &asteroid_materials,
+            coord,
+            chunks.destroyed.get(&coord),
+        );
+        chunks.loaded.insert(coord, entities);
     }
 }
 
-fn random_vec3(min: f32, max: f32) -> Vec3 {
-    let mut rng = rand::thread_rng();
-    Vec3::new(
-        rng.gen_range(min..max),
-        rng.gen_range(min..max),
-        rng.gen_range(min..max),
-    )
+/// Generates the chunk at `coord` of every field from their seeds, skipping the asteroids
+/// destroyed on earlier visits.
+fn spawn_chunk(
+    commands: &mut Commands,
+    field: &AsteroidField,
+    layout: &FieldLayout,
+    variants: &[AsteroidVariant],
+    asteroid_materials: &AsteroidMaterials,
+    coord: IVec3,
+    destroyed: Option<&HashSet<(u32, u32)>>,
+) -> Vec<Entity> {
+    let mut entities = Vec::new();
+    for (field_index, config) in (0..).zip(&layout.fields) {
+        let asteroids = config.asteroids(field.field_seed(config), field.chunk_size, coord);
+        for (index, asteroid) in asteroids {
+            if asteroid.translation.length() < field.clear_radius
+                || destroyed.is_some_and(|destroyed| destroyed.contains(&(field_index, index)))
+            {
+                continue;
+            }
+            let variant = &variants[asteroid.variant];
+            let entity = commands
+                .spawn((
+                    Asteroid,
+                    AsteroidId {
+                        chunk: coord,
+                        field: field_index,
+                        index,
+                    },
+                    Targetable,
+                    asteroid.tier,
+                    AsteroidLod {
+                        variant: asteroid.variant,
+                        level: 0,
+                    },
+                    Mesh3d(variant.lods[0].clone()),
+                    MeshMaterial3d(asteroid_materials.get(asteroid.tier).clone()),
+                    // Seeds the surface variation in `asteroid.wgsl`.
+                    MeshTag(asteroid.tag),
+                    RigidBody::Static,
+                    variant.collider.clone(),
+                    Health {
+                        value: Gauge::new(asteroid.tier.max_health()),
+                    },
+                    Transform {
+                        translation: asteroid.translation,
+                        rotation: asteroid.rotation,
+                        scale: asteroid.scale,
+                    },
+                ))
+                .id();
+            entities.push(entity);
+        }
+    }
+    entities
 }
 
-fn random_vec3_in_sphere() -> Vec3 {
-    let radius = 300.0;
-    let mut rng = rand::thread_rng();
-    let r = radius * rng.r#gen::<f32>().cbrt();
-
-    let theta = rng.gen_range(0.0..2.0 * std::f32::consts::PI);
-    let phi = rng.gen_range(0.0..std::f32::consts::PI);
-
-    let x = r * theta.sin() * phi.sin();
-    let y = r * theta.cos() * phi.sin();
-    let z = r * phi.cos();
+// Runs between damage and despawning, while depleted asteroids are still around.
+fn record_destroyed_asteroids(
+    asteroid_query: Query<(&AsteroidId, &Health), Changed<Health>>,
+    mut chunks: ResMut<AsteroidChunks>,
+) {
+    for (id, health) in &asteroid_query {
+        if health.value.current <= 0.0 {
+            chunks
+                .destroyed
+                .entry(id.chunk)
+                .or_default()
+                .insert((id.field, id.index));
+        }
+    }
+}
 
-    Vec3::new(x, y, z)
+// Runs between damage and despawning, while depleted asteroids are still around.
+fn collect_ore(
+    asteroid_query: Query<(&OreTier, &Health), Changed<Health>>,
+    mut cargo_query: Query<&mut Cargo, With<Player>>,
+) {
+    let Ok(mut cargo) = cargo_query.single_mut() else {
+        return;
+    };
+    for (tier, health) in &asteroid_query {
+        if health.value.current <= 0.0 {
+            cargo.ore += tier.ore_yield();
+        }
+    }
 }
@@ -0,0 +1,104 @@
+use bevy::prelude::*;
+
+use crate::{
+    core::{
+        asteroid::{
+            AsteroidId, AsteroidImpostorMaterial, AsteroidLod, AsteroidMaterial, OreTier,
+            IMPOSTOR_LEVEL,
+        },
+        main_camera::MainCamera,
+        quality::{AsteroidLodSettings, QualityPreset},
+    },
+    resources::{asteroid_materials::AsteroidMaterials, asteroid_meshes::AsteroidMeshes},
+};
+
+pub struct AsteroidLodPlugin;
+
+impl Plugin for AsteroidLodPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugins(MaterialPlugin::<AsteroidImpostorMaterial> {
+            // Impostors are too far out to land in a shadow cascade.
+            shadows_enabled: false,
+            ..default()
+        })
+        .init_resource::<QualityPreset>()
+        .init_resource::<AsteroidLodSettings>()
+        .add_systems(
+            Update,
+            (
+                toggle_quality_preset,
+                apply_quality_preset.run_if(resource_changed::<QualityPreset>),
+                update_asteroid_lods,
+            )
+                .chain(),
+        );
+    }
+}
+
+fn toggle_quality_preset(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    mut quality_preset: ResMut<QualityPreset>,
+) {
+    if keyboard_input.just_pressed(KeyCode::F9) {
+        *quality_preset = quality_preset.next();
+        info!("quality preset: {:?}", *quality_preset);
+    }
+}
+
+fn apply_quality_preset(
+    quality_preset: Res<QualityPreset>,
+    mut lod_settings: ResMut<AsteroidLodSettings>,
+) {
+    *lod_settings = quality_preset.asteroid_lod();
+}
+
+fn update_asteroid_lods(
+    mut commands: Commands,
+    lod_settings: Res<AsteroidLodSettings>,
+    asteroid_meshes: Res<AsteroidMeshes>,
+    asteroid_materials: Res<AsteroidMaterials>,
+    camera_query: Query<&GlobalTransform, With<MainCamera>>,
+    mut asteroid_query: Query<(
+        Entity,
+        &Transform,
+        &OreTier,
+        &AsteroidId,
+        &mut AsteroidLod,
+        &mut Mesh3d,
+    )>,
+) {
+    let Ok(camera_transform) = camera_query.single() else {
+        return;
+    };
+    let camera_position = camera_transform.translation();
+    for (entity, transform, tier, id, mut lod, mut mesh) in &mut asteroid_query {
+        // Larger asteroids keep their detail further out.
+        let distance =
+            transform.translation.distance(camera_position) / transform.scale.max_element();
+        let level = lod_settings.level(lod.level, distance);
+        if level == lod.level {
+            continue;
+        }
+        // The asteroid may be destroyed or its chunk unloaded before these commands apply.
+        if level == IMPOSTOR_LEVEL {
+            mesh.0 = asteroid_meshes.impostor.clone();
+            commands
+                .entity(entity)
+                .try_remove::<MeshMaterial3d<AsteroidMaterial>>()
+                .try_insert(MeshMaterial3d(asteroid_materials.impostor(*tier).clone()));
+        } else {
+            // The chunk may have been unloaded this frame, with its asteroids not despawned yet.
+            let Some(variant) = asteroid_meshes.variant(id.chunk, lod.variant) else {
+                continue;
+            };
+            mesh.0 = variant.lods[level].clone();
+            if lod.level == IMPOSTOR_LEVEL {
+                commands
+                    .entity(entity)
+                    .try_remove::<MeshMaterial3d<AsteroidImpostorMaterial>>()
+                    .try_insert(MeshMaterial3d(asteroid_materials.get(*tier).clone()));
+            }
+        }
+        lod.level = level;
+    }
+}
@@ -1,201 +1,166 @@
+use std::f32::consts::TAU;
+
+use avian3d::prelude::LinearVelocity;
 use bevy::{
-    core_pipeline::{
-        core_3d::graph::{Core3d, Node3d},
-        FullscreenShader,
-    },
     ecs::query::QueryItem,
     prelude::*,
     render::{
-        extract_component::{
-            ComponentUniforms, DynamicUniformIndex, ExtractComponent, ExtractComponentPlugin,
-            UniformComponentPlugin,
-        },
-        render_graph::{
-            NodeRunError, RenderGraphContext, RenderGraphExt, RenderLabel, ViewNode, ViewNodeRunner,
-        },
-        render_resource::{
-            binding_types::{sampler, texture_2d, uniform_buffer},
-            BindGroupEntries, BindGroupLayout, BindGroupLayoutEntries, CachedRenderPipelineId,
-            ColorTargetState, ColorWrites, FragmentState, MultisampleState, Operations,
-            PipelineCache, PrimitiveState, RenderPassColorAttachment, RenderPassDescriptor,
-            RenderPipelineDescriptor, Sampler, SamplerBindingType, SamplerDescriptor, ShaderStages,
-            ShaderType, TextureFormat, TextureSampleType,
-        },
-        renderer::{RenderContext, RenderDevice},
-        view::ViewTarget,
-        RenderApp, RenderStartup,
+        extract_component::ExtractComponent,
+        render_graph::RenderLabel,
+        render_resource::{BindingResource, ShaderType},
     },
 };
 
-use crate::core::main_camera::MainCamera;
+use crate::{
+    core::{
+        accessibility::Accessibility,
+        chromatic_abberation::{AbberationPulse, ChromaticAbberationController},
+        damage::{Damage, Invulnerable},
+        main_camera::MainCamera,
+        player::Player,
+        stats::Health,
+    },
+    plugins::post_process::{PostProcessPlugin, PostProcessSettings, PostProcessStage},
+};
 
 pub struct ChromaticAbberationPlugin;
 
+// Lateral screen speed below which the directional mode keeps its last direction.
+const DIRECTION_MIN_SPEED: f32 = 1.0;
+const DIRECTION_SMOOTHING: f32 = 5.0;
+
 fn add_components_main_camera(
     mut commands: Commands,
     main_camera_query: Query<Entity, With<MainCamera>>,
 ) {
     if let Ok(main_camera_entity) = main_camera_query.single() {
-        commands
-            .entity(main_camera_entity)
-            .insert(ChromaticAbberationSettings {
+        commands.entity(main_camera_entity).insert((
+            ChromaticAbberationSettings {
                 intensity: 0.01,
                 distance_exponent: 4.0,
-            });
+                mode: 0,
+                direction: Vec2::X,
+            },
+            ChromaticAbberationController::default(),
+        ));
     }
 }
 
 impl Plugin for ChromaticAbberationPlugin {
     fn build(&self, app: &mut App) {
-        app.add_plugins((
-            ExtractComponentPlugin::<ChromaticAbberationSettings>::default(),
-            UniformComponentPlugin::<ChromaticAbberationSettings>::default(),
-        ))
-        .add_systems(Startup, add_components_main_camera);
-        let Some(render_app) = app.get_sub_app_mut(RenderApp) else {
-            return;
-        };
-        render_app
-            .add_systems(RenderStartup, init_pipeline)
-            .add_render_graph_node::<ViewNodeRunner<ChromaticAbberationNode>>(
-                Core3d,
-                ChromaticAbberationLabel,
-            )
-            .add_render_graph_edges(
-                Core3d,
-                (
-                    Node3d::Tonemapping,
-                    ChromaticAbberationLabel,
-                    Node3d::EndMainPassPostProcessing,
-                ),
+        app.add_plugins(PostProcessPlugin::<ChromaticAbberationSettings>::default())
+            .add_message::<AbberationPulse>()
+            .init_resource::<Accessibility>()
+            .add_systems(Startup, add_components_main_camera)
+            .add_systems(
+                Update,
+                (toggle_chromatic_abberation, update_chromatic_abberation).chain(),
             );
     }
 }
 
+fn toggle_chromatic_abberation(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    mut accessibility: ResMut<Accessibility>,
+    mut controller_query: Query<&mut ChromaticAbberationController>,
+) {
+    if keyboard_input.just_pressed(KeyCode::F7) {
+        accessibility.chromatic_abberation = !accessibility.chromatic_abberation;
+    }
+    if keyboard_input.just_pressed(KeyCode::F8) {
+        for mut controller in &mut controller_query {
+            controller.mode = controller.mode.next();
+        }
+    }
+}
+
+fn update_chromatic_abberation(
+    accessibility: Res<Accessibility>,
+    time: Res<Time>,
+    mut damage: MessageReader<Damage>,
+    mut pulses: MessageReader<AbberationPulse>,
+    player_query: Query<(Entity, &Health, &LinearVelocity, Has<Invulnerable>), With<Player>>,
+    mut camera_query: Query<
+        (
+            &Transform,
+            &mut ChromaticAbberationController,
+            &mut ChromaticAbberationSettings,
+        ),
+        With<MainCamera>,
+    >,
+) {
+    let Ok((camera_transform, mut controller, mut settings)) = camera_query.single_mut() else {
+        return;
+    };
+    let player = player_query.single().ok();
+    // Hits dodged while invulnerable don't pulse, matching `apply_damage`.
+    let damage_taken: f32 = damage
+        .read()
+        .filter(|damage| {
+            player.is_some_and(|(entity, _, _, invulnerable)| {
+                damage.target == entity && !invulnerable
+            })
+        })
+        .map(|damage| damage.amount)
+        .sum();
+    let pulse: f32 = pulses.read().map(|pulse| pulse.0).sum();
+    controller.pulse = (controller.pulse + damage_taken * controller.damage_pulse + pulse
+        - controller.decay * time.delta_secs())
+    .max(0.0);
+
+    let mut intensity = controller.base_intensity + controller.pulse;
+    if let Some((_, health, velocity, _)) = player {
+        let health = health.value.normalized();
+        if health < controller.low_health_threshold {
+            let severity = 1.0 - health / controller.low_health_threshold;
+            let throb = 0.5
+                + 0.5 * (time.elapsed_secs_wrapped() * TAU * controller.low_health_frequency).sin();
+            intensity += controller.low_health_intensity * severity * throb;
+        }
+        let screen_velocity = camera_transform.rotation.inverse() * velocity.0;
+        let lateral = Vec2::new(screen_velocity.x, -screen_velocity.y);
+        if lateral.length() > DIRECTION_MIN_SPEED {
+            settings.direction = settings
+                .direction
+                .lerp(
+                    lateral.normalize(),
+                    1.0 - (-DIRECTION_SMOOTHING * time.delta_secs()).exp(),
+                )
+                .normalize_or(Vec2::X);
+        }
+    }
+    settings.intensity = if accessibility.chromatic_abberation {
+        intensity.min(controller.max_intensity)
+    } else {
+        0.0
+    };
+    settings.mode = controller.mode as u32;
+}
+
 #[derive(Component, Default, Clone, Copy, ExtractComponent, ShaderType)]
 pub struct ChromaticAbberationSettings {
     pub intensity: f32,
     pub distance_exponent: f32,
+    /// A `ChromaticAbberationMode`, as written by `update_chromatic_abberation`.
+    pub mode: u32,
+    /// Screen space split direction for `ChromaticAbberationMode::Directional`.
+    pub direction: Vec2,
 }
 
-#[derive(Debug, Hash, PartialEq, Eq, Clone, RenderLabel)]
-struct ChromaticAbberationLabel;
-
-#[derive(Default)]
-struct ChromaticAbberationNode;
-
-impl ViewNode for ChromaticAbberationNode {
-    type ViewQuery = (
-        &'static ViewTarget,
-        &'static ChromaticAbberationSettings,
-        &'static DynamicUniformIndex<ChromaticAbberationSettings>,
-    );
-
-    fn run(
-        &self,
-        _graph: &mut RenderGraphContext,
-        render_context: &mut RenderContext,
-        (view_target, _post_process_settings, settings_index): QueryItem<Self::ViewQuery>,
-        world: &World,
-    ) -> Result<(), NodeRunError> {
-        let post_process_pipeline = world.resource::<ChromaticAbberationPipeline>();
-        let pipeline_cache = world.resource::<PipelineCache>();
-
-        let Some(pipeline) = pipeline_cache.get_render_pipeline(post_process_pipeline.pipeline_id)
-        else {
-            return Ok(());
-        };
-
-        let settings_uniforms = world.resource::<ComponentUniforms<ChromaticAbberationSettings>>();
-        let Some(settings_binding) = settings_uniforms.uniforms().binding() else {
-            return Ok(());
-        };
-
-        let post_process = view_target.post_process_write();
-        let bind_group = render_context.render_device().create_bind_group(
-            "post_process_bind_group",
-            &post_process_pipeline.layout,
-            &BindGroupEntries::sequential((
-                post_process.source,
-                &post_process_pipeline.sampler,
-                settings_binding.clone(),
-            )),
-        );
-
-        let mut render_pass = render_context.begin_tracked_render_pass(RenderPassDescriptor {
-            label: Some("post_process_pass"),
-            color_attachments: &[Some(RenderPassColorAttachment {
-                view: post_process.destination,
-                resolve_target: None,
-                ops: Operations::default(),
-                depth_slice: None,
-            })],
-            depth_stencil_attachment: None,
-            timestamp_writes: None,
-            occlusion_query_set: None,
-        });
-
-        render_pass.set_render_pipeline(pipeline);
-        render_pass.set_bind_group(0, &bind_group, &[settings_index.index()]);
-        render_pass.draw(0..3, 0..1);
-
-        Ok(())
+impl PostProcessSettings for ChromaticAbberationSettings {
+    const SHADER: &'static str = "shaders/chromatic_abberation.wgsl";
+    const STAGE: PostProcessStage = PostProcessStage::Image;
+    const ORDER: i32 = 10;
+    type Label = ChromaticAbberationLabel;
+    type ViewQuery = ();
+
+    fn extra_bindings<'w>(
+        _view: QueryItem<'w, '_, Self::ViewQuery>,
+        _world: &'w World,
+    ) -> Option<Vec<BindingResource<'w>>> {
+        Some(Vec::new())
     }
 }
 
-fn init_pipeline(
-    mut commands: Commands,
-    render_device: Res<RenderDevice>,
-    asset_server: Res<AssetServer>,
-    fullscreen_shader: Res<FullscreenShader>,
-    pipeline_cache: Res<PipelineCache>,
-) {
-    let layout = render_device.create_bind_group_layout(
-        "post_process_bind_group_layout",
-        &BindGroupLayoutEntries::sequential(
-            ShaderStages::FRAGMENT,
-            (
-                texture_2d(TextureSampleType::Float { filterable: true }),
-                sampler(SamplerBindingType::Filtering),
-                uniform_buffer::<ChromaticAbberationSettings>(true),
-            ),
-        ),
-    );
-
-    let sampler = render_device.create_sampler(&SamplerDescriptor::default());
-    let shader = asset_server.load("shaders/chromatic_abberation.wgsl");
-    let vertex_state = fullscreen_shader.to_vertex_state();
-    let pipeline_id = pipeline_cache.queue_render_pipeline(RenderPipelineDescriptor {
-        label: Some("chromatic_abberation_pipeline".into()),
-        layout: vec![layout.clone()],
-        vertex: vertex_state,
-        fragment: Some(FragmentState {
-            shader,
-            shader_defs: vec![],
-            entry_point: Some("fragment".into()),
-            targets: vec![Some(ColorTargetState {
-                format: TextureFormat::bevy_default(),
-                blend: None,
-                write_mask: ColorWrites::ALL,
-            })],
-        }),
-        primitive: PrimitiveState::default(),
-        depth_stencil: None,
-        multisample: MultisampleState::default(),
-        push_constant_ranges: vec![],
-        zero_initialize_workgroup_memory: true,
-    });
-
-    commands.insert_resource(ChromaticAbberationPipeline {
-        layout,
-        sampler,
-        pipeline_id,
-    });
-}
-
-#[derive(Resource)]
-struct ChromaticAbberationPipeline {
-    layout: BindGroupLayout,
-    sampler: Sampler,
-    pipeline_id: CachedRenderPipelineId,
-}
+#[derive(Debug, Hash, PartialEq, Eq, Clone, Default, RenderLabel)]
+pub struct ChromaticAbberationLabel;
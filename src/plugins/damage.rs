@@ -0,0 +1,113 @@
+use avian3d::prelude::{CollisionStart, LinearVelocity};
+use bevy::prelude::*;
+
+use crate::{
+    core::{
+        damage::{Damage, Destroyed, Invulnerable},
+        particles::SpawnParticles,
+        player::Player,
+        projectile::Projectile,
+        stats::{Health, Shield},
+    },
+    resources::particle_effects::ParticleEffects,
+};
+
+pub struct DamagePlugin;
+
+impl Plugin for DamagePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_message::<Damage>()
+            .add_message::<Destroyed>()
+            .add_systems(
+                Update,
+                (projectile_hits, apply_damage, destroy_depleted).chain(),
+            )
+            .add_systems(Update, update_invulnerability);
+    }
+}
+
+fn projectile_hits(
+    mut commands: Commands,
+    mut collisions: MessageReader<CollisionStart>,
+    mut damage: MessageWriter<Damage>,
+    mut particles: MessageWriter<SpawnParticles>,
+    particle_effects: Res<ParticleEffects>,
+    projectile_query: Query<(&Projectile, &Transform, &LinearVelocity)>,
+) {
+    for collision in collisions.read() {
+        let hits = [
+            (
+                collision.collider1,
+                collision.body2.unwrap_or(collision.collider2),
+            ),
+            (
+                collision.collider2,
+                collision.body1.unwrap_or(collision.collider1),
+            ),
+        ];
+        for (projectile_entity, target) in hits {
+            if let Ok((projectile, transform, velocity)) = projectile_query.get(projectile_entity) {
+                damage.write(Damage {
+                    target,
+                    amount: projectile.damage,
+                });
+                // Sparks fly back out towards the shooter.
+                particles.write(SpawnParticles {
+                    effect: particle_effects.impact_sparks.clone(),
+                    transform: Transform::from_translation(transform.translation)
+                        .looking_to(-velocity.0, Vec3::Y),
+                    velocity: Vec3::ZERO,
+                });
+                commands.entity(projectile_entity).try_despawn();
+            }
+        }
+    }
+}
+
+pub fn apply_damage(
+    mut damage: MessageReader<Damage>,
+    mut target_query: Query<(&mut Health, Option<&mut Shield>), Without<Invulnerable>>,
+) {
+    for Damage { target, amount } in damage.read() {
+        let Ok((mut health, shield)) = target_query.get_mut(*target) else {
+            continue;
+        };
+        let mut remaining = *amount;
+        if let Some(mut shield) = shield {
+            let absorbed = remaining.min(shield.value.current);
+            shield.value.current -= absorbed;
+            remaining -= absorbed;
+        }
+        health.value.current = (health.value.current - remaining).max(0.0);
+    }
+}
+
+// The player has no respawn flow yet, so it stays in play at zero health.
+pub fn destroy_depleted(
+    mut commands: Commands,
+    mut destroyed: MessageWriter<Destroyed>,
+    depleted_query: Query<(Entity, &Health, &Transform, Option<&LinearVelocity>), Without<Player>>,
+) {
+    for (entity, health, transform, velocity) in &depleted_query {
+        if health.value.current <= 0.0 {
+            destroyed.write(Destroyed {
+                transform: *transform,
+                velocity: velocity.map_or(Vec3::ZERO, |velocity| velocity.0),
+            });
+            commands.entity(entity).despawn();
+        }
+    }
+}
+
+fn update_invulnerability(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut invulnerable_query: Query<(Entity, &mut Invulnerable)>,
+) {
+    for (entity, mut invulnerable) in invulnerable_query.iter_mut() {
+        invulnerable.remaining -= time.delta_secs();
+        if invulnerable.remaining <= 0.0 {
+            commands.entity(entity).remove::<Invulnerable>();
+        }
+    }
+}
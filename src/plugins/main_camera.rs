@@ -1,19 +1,52 @@
+use avian3d::prelude::LinearVelocity;
 use bevy::{
     core_pipeline::prepass::{DepthPrepass, NormalPrepass},
+    input::mouse::{MouseMotion, MouseWheel},
+    post_process::bloom::Bloom,
     prelude::*,
+    render::view::Hdr,
 };
 
-use crate::core::{main_camera::MainCamera, player::Player};
+use crate::core::{
+    damage::{Damage, Destroyed, Invulnerable},
+    main_camera::{CameraMode, CameraOrbit, CameraShake, CameraSpring, CameraTrauma, MainCamera},
+    player::Player,
+    target::LockedTarget,
+};
 
 pub struct MainCameraPlugin;
 
 const TARGET_OFFSET: Vec3 = Vec3::new(0.0, 2.0, 18.0);
+const COCKPIT_OFFSET: Vec3 = Vec3::new(0.0, 0.4, -0.6);
+const CINEMATIC_SIDE_OFFSET: Vec3 = Vec3::new(12.0, 3.0, 6.0);
+const ORBIT_SENSITIVITY: f32 = 0.005;
+const ORBIT_DISTANCE_RANGE: (f32, f32) = (5.0, 60.0);
+const DAMAGE_TRAUMA_SCALE: f32 = 0.05;
+/// Trauma of an explosion right next to the player, fading out linearly to none at
+/// `EXPLOSION_TRAUMA_RANGE`.
+const EXPLOSION_TRAUMA: f32 = 0.6;
+const EXPLOSION_TRAUMA_RANGE: f32 = 80.0;
+const COCKPIT_STIFFNESS_SCALE: f32 = 4.0;
 
 impl Plugin for MainCameraPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(PreStartup, spawn_main_camera)
+        app.add_message::<CameraTrauma>()
+            .add_systems(PreStartup, spawn_main_camera)
             .add_systems(Startup, add_components_main_camera)
-            .add_systems(Update, camera_follow);
+            .add_systems(
+                Update,
+                (
+                    handle_camera_input,
+                    damage_trauma,
+                    explosion_trauma,
+                    add_camera_trauma,
+                    remove_camera_shake,
+                    camera_follow,
+                    camera_speed_fov,
+                    apply_camera_shake,
+                )
+                    .chain(),
+            );
     }
 }
 
@@ -29,28 +62,284 @@ fn add_components_main_camera(
         commands.entity(main_camera_entity).insert((
             Camera3d::default(),
             Transform::from_translation(TARGET_OFFSET),
+            Hdr,
+            Bloom::NATURAL,
             DepthPrepass,
             NormalPrepass,
+            CameraMode::default(),
+            CameraOrbit::default(),
+            CameraSpring::default(),
+            CameraShake::default(),
         ));
     }
 }
 
+fn handle_camera_input(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    mut mouse_movement: MessageReader<MouseMotion>,
+    mut mouse_wheel: MessageReader<MouseWheel>,
+    mut camera_query: Query<(&mut CameraMode, &mut CameraOrbit), With<MainCamera>>,
+) {
+    if let Ok((mut camera_mode, mut camera_orbit)) = camera_query.single_mut() {
+        if keyboard_input.just_pressed(KeyCode::KeyV) {
+            *camera_mode = camera_mode.next();
+        }
+        if *camera_mode != CameraMode::Orbit {
+            return;
+        }
+        if camera_mode.is_orbiting(&keyboard_input) {
+            for event in mouse_movement.read() {
+                camera_orbit.yaw -= event.delta.x * ORBIT_SENSITIVITY;
+                camera_orbit.pitch =
+                    (camera_orbit.pitch - event.delta.y * ORBIT_SENSITIVITY).clamp(-1.5, 1.5);
+            }
+        }
+        for event in mouse_wheel.read() {
+            camera_orbit.distance = (camera_orbit.distance - event.y)
+                .clamp(ORBIT_DISTANCE_RANGE.0, ORBIT_DISTANCE_RANGE.1);
+        }
+    }
+}
+
 fn camera_follow(
+    player_query: Query<&Transform, (With<Player>, Without<MainCamera>)>,
+    player_velocity_query: Query<&LinearVelocity, With<Player>>,
+    locked_target_query: Query<&LockedTarget, With<Player>>,
+    mut camera_query: Query<
+        (&mut Transform, &mut CameraSpring, &CameraMode, &CameraOrbit),
+        With<MainCamera>,
+    >,
+    target_query: Query<&GlobalTransform>,
+    time: Res<Time>,
+) {
+    if let Ok(player_transform) = player_query.single()
+        && let Ok(player_velocity) = player_velocity_query.single()
+    {
+        let target_position = locked_target_query
+            .single()
+            .ok()
+            .and_then(|locked_target| target_query.get(locked_target.0).ok())
+            .map(|target_transform| target_transform.translation());
+        for (mut camera_transform, mut camera_spring, camera_mode, camera_orbit) in
+            &mut camera_query
+        {
+            let speed_factor = camera_spring.speed_factor(player_velocity.length());
+            let (mut desired_position, desired_rotation) = desired_pose(
+                player_transform,
+                target_position,
+                camera_mode,
+                camera_orbit,
+                1.0 + (camera_spring.max_distance_scale - 1.0) * speed_factor,
+            );
+            let mut stiffness = camera_spring.stiffness;
+            let mut rotation_stiffness = camera_spring.rotation_stiffness;
+            match camera_mode {
+                CameraMode::Cockpit => {
+                    stiffness *= COCKPIT_STIFFNESS_SCALE;
+                    rotation_stiffness *= COCKPIT_STIFFNESS_SCALE;
+                }
+                CameraMode::Chase | CameraMode::Orbit => {
+                    desired_position += player_velocity.0 * camera_spring.look_ahead;
+                }
+                CameraMode::Cinematic => {}
+            }
+            let (position, velocity) = critically_damped_spring(
+                camera_transform.translation,
+                camera_spring.velocity,
+                desired_position,
+                player_transform.rotation,
+                stiffness,
+                time.delta_secs(),
+            );
+            camera_transform.translation = position;
+            camera_spring.velocity = velocity;
+            camera_transform.rotation = camera_transform.rotation.slerp(
+                desired_rotation,
+                1.0 - (-rotation_stiffness * time.delta_secs()).exp(),
+            );
+        }
+    }
+}
+
+fn camera_speed_fov(
+    player_query: Query<&LinearVelocity, With<Player>>,
+    mut camera_query: Query<(&mut Projection, &CameraSpring), With<MainCamera>>,
+) {
+    if let Ok(player_velocity) = player_query.single() {
+        for (mut projection, camera_spring) in &mut camera_query {
+            if let Projection::Perspective(perspective) = projection.as_mut() {
+                let speed_factor = camera_spring.speed_factor(player_velocity.length());
+                perspective.fov = camera_spring
+                    .base_fov
+                    .lerp(camera_spring.max_fov, speed_factor);
+            }
+        }
+    }
+}
+
+// Exact integration of a critically damped spring, so the result does not depend on frame rate.
+// Stiffness is given per axis in the local space of `frame`.
+fn critically_damped_spring(
+    position: Vec3,
+    velocity: Vec3,
+    target: Vec3,
+    frame: Quat,
+    stiffness: Vec3,
+    delta_secs: f32,
+) -> (Vec3, Vec3) {
+    let to_local = frame.inverse();
+    let change = to_local * (position - target);
+    let local_velocity = to_local * velocity;
+    let decay = (-stiffness * delta_secs).exp();
+    let temp = (local_velocity + stiffness * change) * delta_secs;
+    let local_velocity = (local_velocity - stiffness * temp) * decay;
+    let change = (change + temp) * decay;
+    (target + frame * change, frame * local_velocity)
+}
+
+fn desired_pose(
+    player_transform: &Transform,
+    target_position: Option<Vec3>,
+    camera_mode: &CameraMode,
+    camera_orbit: &CameraOrbit,
+    distance_scale: f32,
+) -> (Vec3, Quat) {
+    let player_position = player_transform.translation;
+    let player_up = player_transform.up().as_vec3();
+    match camera_mode {
+        CameraMode::Chase => (
+            player_position
+                + player_transform
+                    .rotation
+                    .mul_vec3(TARGET_OFFSET * distance_scale),
+            player_transform.rotation,
+        ),
+        CameraMode::Cockpit => (
+            player_position + player_transform.rotation.mul_vec3(COCKPIT_OFFSET),
+            player_transform.rotation,
+        ),
+        CameraMode::Orbit => {
+            let orbit_rotation = player_transform.rotation
+                * Quat::from_euler(EulerRot::YXZ, camera_orbit.yaw, camera_orbit.pitch, 0.0);
+            let position = player_position
+                + orbit_rotation.mul_vec3(Vec3::Z * camera_orbit.distance * distance_scale);
+            (position, looking_at(position, player_position, player_up))
+        }
+        CameraMode::Cinematic => {
+            let Some(target_position) = target_position else {
+                let position =
+                    player_position + player_transform.rotation.mul_vec3(CINEMATIC_SIDE_OFFSET);
+                return (position, looking_at(position, player_position, player_up));
+            };
+            // Step out sideways from the ship-target line far enough to fit both in frame.
+            let midpoint = (player_position + target_position) * 0.5;
+            let separation = player_position.distance(target_position);
+            let line = (target_position - player_position).normalize_or(Vec3::NEG_Z);
+            let side = line.cross(player_up).normalize_or(Vec3::X);
+            let distance = separation.max(10.0) * 0.5 / (std::f32::consts::FRAC_PI_8).tan();
+            let position =
+                midpoint + side * distance + player_up * distance * 0.25 - line * separation * 0.25;
+            (position, looking_at(position, midpoint, player_up))
+        }
+    }
+}
+
+fn looking_at(position: Vec3, target: Vec3, up: Vec3) -> Quat {
+    Transform::from_translation(position)
+        .looking_at(target, up)
+        .rotation
+}
+
+fn damage_trauma(
+    mut damage: MessageReader<Damage>,
+    mut trauma: MessageWriter<CameraTrauma>,
+    player_query: Query<Entity, (With<Player>, Without<Invulnerable>)>,
+) {
+    // Hits dodged while invulnerable don't shake the camera, matching `apply_damage`.
+    let Ok(player_entity) = player_query.single() else {
+        damage.clear();
+        return;
+    };
+    for Damage { target, amount } in damage.read() {
+        if *target == player_entity {
+            trauma.write(CameraTrauma(amount * DAMAGE_TRAUMA_SCALE));
+        }
+    }
+}
+
+fn explosion_trauma(
+    mut destroyed: MessageReader<Destroyed>,
+    mut trauma: MessageWriter<CameraTrauma>,
     player_query: Query<&Transform, With<Player>>,
-    mut camera_query: Query<&mut Transform, (With<MainCamera>, Without<Player>)>,
+) {
+    let Ok(player_transform) = player_query.single() else {
+        return;
+    };
+    for Destroyed { transform, .. } in destroyed.read() {
+        let distance = transform.translation.distance(player_transform.translation);
+        let falloff = 1.0 - distance / EXPLOSION_TRAUMA_RANGE;
+        if falloff > 0.0 {
+            trauma.write(CameraTrauma(EXPLOSION_TRAUMA * falloff));
+        }
+    }
+}
+
+fn add_camera_trauma(
+    mut trauma: MessageReader<CameraTrauma>,
+    mut camera_query: Query<&mut CameraShake, With<MainCamera>>,
+    time: Res<Time>,
+) {
+    for mut camera_shake in &mut camera_query {
+        let added: f32 = trauma.read().map(|trauma| trauma.0).sum();
+        camera_shake.trauma =
+            (camera_shake.trauma + added - camera_shake.decay * time.delta_secs()).clamp(0.0, 1.0);
+    }
+}
+
+fn remove_camera_shake(
+    mut camera_query: Query<(&mut Transform, &mut CameraShake), With<MainCamera>>,
+) {
+    for (mut camera_transform, mut camera_shake) in &mut camera_query {
+        camera_transform.rotation *= camera_shake.applied_rotation.inverse();
+        camera_transform.translation -= camera_shake.applied_offset;
+        camera_shake.applied_offset = Vec3::ZERO;
+        camera_shake.applied_rotation = Quat::IDENTITY;
+    }
+}
+
+fn apply_camera_shake(
+    mut camera_query: Query<(&mut Transform, &mut CameraShake), With<MainCamera>>,
     time: Res<Time>,
 ) {
-    if let Ok(player_transform) = player_query.single() {
-        for mut camera_transform in &mut camera_query {
-            let target_offset_relative_to_player =
-                player_transform.rotation.mul_vec3(TARGET_OFFSET);
-            let target_position = player_transform.translation + target_offset_relative_to_player;
-            camera_transform.translation = camera_transform
-                .translation
-                .lerp(target_position, 10.0 * time.delta_secs());
-            camera_transform.rotation = camera_transform
-                .rotation
-                .slerp(player_transform.rotation, 10.0 * time.delta_secs());
+    for (mut camera_transform, mut camera_shake) in &mut camera_query {
+        let shake = camera_shake.trauma * camera_shake.trauma;
+        if shake <= 0.0 {
+            continue;
         }
+        let t = time.elapsed_secs_wrapped() * camera_shake.frequency;
+        let offset = Vec2::new(noise_1d(t, 0.0), noise_1d(t, 1.0)) * camera_shake.max_offset;
+        let rotation = Vec3::new(noise_1d(t, 2.0), noise_1d(t, 3.0), noise_1d(t, 4.0))
+            * camera_shake.max_rotation;
+        let applied_offset =
+            (camera_transform.right() * offset.x + camera_transform.up() * offset.y) * shake;
+        let applied_rotation = Quat::from_euler(
+            EulerRot::YXZ,
+            rotation.x * shake,
+            rotation.y * shake,
+            rotation.z * shake,
+        );
+        camera_transform.translation += applied_offset;
+        camera_transform.rotation *= applied_rotation;
+        camera_shake.applied_offset = applied_offset;
+        camera_shake.applied_rotation = applied_rotation;
     }
 }
+
+// Smooth 1D value noise in [-1, 1], one independent stream per seed.
+fn noise_1d(t: f32, seed: f32) -> f32 {
+    let hash = |n: f32| ((n * 12.9898 + seed * 78.233).sin() * 43758.545).rem_euclid(1.0);
+    let i = t.floor();
+    let f = t - i;
+    let f = f * f * (3.0 - 2.0 * f);
+    (hash(i) + (hash(i + 1.0) - hash(i)) * f) * 2.0 - 1.0
+}
@@ -1,12 +1,19 @@
 pub mod asteroid;
+pub mod asteroid_lod;
 pub mod chromatic_abberation;
+pub mod damage;
 pub mod main_camera;
+pub mod nebula_effects;
 pub mod outline;
+pub mod particles;
 pub mod player;
 pub mod player_controller;
+pub mod post_process;
 pub mod procedural_skybox;
 pub mod projectile;
+pub mod ron_asset;
 pub mod scene_lighting;
+pub mod targeting;
 pub mod upgrade;
 pub mod volumetric_nebula;
 pub mod weapon;
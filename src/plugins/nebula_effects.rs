@@ -0,0 +1,89 @@
+use avian3d::prelude::LinearVelocity;
+use bevy::prelude::*;
+
+use crate::{
+    core::{
+        main_camera::MainCamera,
+        nebula::{NebulaExposure, NebulaVolume},
+        player::Player,
+        stats::Shield,
+    },
+    plugins::volumetric_nebula::VolumetricNebulaSettings,
+};
+
+pub struct NebulaEffectsPlugin;
+
+impl Plugin for NebulaEffectsPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(NebulaEffects::default())
+            .add_systems(Startup, add_components_player)
+            .add_systems(
+                Update,
+                (update_nebula_exposure, nebula_shield_disruption).chain(),
+            )
+            .add_systems(FixedUpdate, nebula_drag);
+    }
+}
+
+#[derive(Resource)]
+pub struct NebulaEffects {
+    pub sensor_dampening: f32,
+    pub shield_disruption: f32,
+    pub drag: f32,
+}
+
+impl Default for NebulaEffects {
+    fn default() -> Self {
+        Self {
+            sensor_dampening: 0.6,
+            shield_disruption: 5.0,
+            drag: 1.5,
+        }
+    }
+}
+
+fn add_components_player(mut commands: Commands, player_query: Query<Entity, With<Player>>) {
+    if let Ok(player_entity) = player_query.single() {
+        commands
+            .entity(player_entity)
+            .insert(NebulaExposure::default());
+    }
+}
+
+fn update_nebula_exposure(
+    effects: Res<NebulaEffects>,
+    camera_query: Query<&VolumetricNebulaSettings, With<MainCamera>>,
+    volume_query: Query<(&GlobalTransform, &NebulaVolume)>,
+    mut exposure_query: Query<(&GlobalTransform, &mut NebulaExposure)>,
+) {
+    if let Ok(settings) = camera_query.single() {
+        for (transform, mut exposure) in &mut exposure_query {
+            let density = settings
+                .density_at(transform.translation(), volume_query)
+                .min(1.0);
+            exposure.density = density;
+            exposure.sensor_factor = 1.0 - effects.sensor_dampening * density;
+        }
+    }
+}
+
+fn nebula_shield_disruption(
+    effects: Res<NebulaEffects>,
+    time: Res<Time>,
+    mut shield_query: Query<(&NebulaExposure, &mut Shield)>,
+) {
+    for (exposure, mut shield) in &mut shield_query {
+        let drain = effects.shield_disruption * exposure.density * time.delta_secs();
+        shield.value.current = (shield.value.current - drain).max(0.0);
+    }
+}
+
+fn nebula_drag(
+    effects: Res<NebulaEffects>,
+    time: Res<Time>,
+    mut velocity_query: Query<(&NebulaExposure, &mut LinearVelocity)>,
+) {
+    for (exposure, mut velocity) in &mut velocity_query {
+        velocity.0 /= 1.0 + effects.drag * exposure.density * time.delta_secs();
+    }
+}
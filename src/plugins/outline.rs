@@ -1,216 +1,197 @@
 use bevy::{
-    core_pipeline::{
-        core_3d::graph::{Core3d, Node3d},
-        prepass::ViewPrepassTextures,
-        FullscreenShader,
-    },
+    camera::{visibility::RenderLayers, RenderTarget},
+    core_pipeline::{prepass::ViewPrepassTextures, tonemapping::Tonemapping},
     ecs::query::QueryItem,
     prelude::*,
     render::{
-        extract_component::{
-            ComponentUniforms, DynamicUniformIndex, ExtractComponent, ExtractComponentPlugin,
-            UniformComponentPlugin,
-        },
-        render_graph::{
-            NodeRunError, RenderGraphContext, RenderGraphExt, RenderLabel, ViewNode, ViewNodeRunner,
-        },
+        extract_component::{ExtractComponent, ExtractComponentPlugin},
+        render_asset::RenderAssets,
+        render_graph::RenderLabel,
         render_resource::{
-            binding_types::{
-                sampler, texture_2d, texture_2d_multisampled, texture_depth_2d_multisampled,
-                uniform_buffer,
-            },
-            BindGroupEntries, BindGroupLayout, BindGroupLayoutEntries, CachedRenderPipelineId,
-            ColorTargetState, ColorWrites, FragmentState, MultisampleState, Operations,
-            PipelineCache, PrimitiveState, RenderPassColorAttachment, RenderPassDescriptor,
-            RenderPipelineDescriptor, Sampler, SamplerBindingType, SamplerDescriptor, ShaderStages,
-            ShaderType, TextureFormat, TextureSampleType,
+            binding_types::{texture_2d, texture_2d_multisampled, texture_depth_2d_multisampled},
+            BindGroupLayoutEntryBuilder, BindingResource, Extent3d, IntoBinding, ShaderType,
+            TextureFormat, TextureSampleType,
         },
-        renderer::{RenderContext, RenderDevice},
-        view::ViewTarget,
-        RenderApp,
+        texture::GpuImage,
     },
+    window::PrimaryWindow,
 };
 
-use crate::core::main_camera::MainCamera;
+use crate::{
+    core::{main_camera::MainCamera, player::Player},
+    plugins::post_process::{PostProcessPlugin, PostProcessSettings, PostProcessStage},
+};
 
 pub struct OutlinePlugin;
 
+const OUTLINE_LAYER: usize = 1;
+
+/// Opts an entity into the outline effect. The outline only follows the entity's own mesh, not
+/// those of its children.
+#[derive(Component)]
+pub struct Outlined;
+
+#[derive(Component, Clone, ExtractComponent)]
+pub struct OutlineMask(Handle<Image>);
+
+#[derive(Component)]
+struct OutlineMaskCamera;
+
 fn add_components_main_camera(
     mut commands: Commands,
+    mut images: ResMut<Assets<Image>>,
     main_camera_query: Query<Entity, With<MainCamera>>,
+    window_query: Query<&Window, With<PrimaryWindow>>,
 ) {
-    if let Ok(main_camera_entity) = main_camera_query.single() {
+    if let Ok(main_camera_entity) = main_camera_query.single()
+        && let Ok(window) = window_query.single()
+    {
+        let mask = images.add(Image::new_target_texture(
+            window.physical_width().max(1),
+            window.physical_height().max(1),
+            TextureFormat::bevy_default(),
+        ));
         commands
             .entity(main_camera_entity)
-            .insert(OutlineSettings { cutoff: 0.5 });
+            .insert((OutlineSettings::default(), OutlineMask(mask.clone())));
+        // Renders only outlined entities so the outline pass can find their silhouettes.
+        commands.spawn((
+            OutlineMaskCamera,
+            Camera3d::default(),
+            Camera {
+                order: -1,
+                target: RenderTarget::from(mask),
+                clear_color: ClearColorConfig::Custom(Color::NONE),
+                ..default()
+            },
+            Tonemapping::None,
+            RenderLayers::layer(OUTLINE_LAYER),
+            Transform::default(),
+            ChildOf(main_camera_entity),
+        ));
+    }
+}
+
+fn add_components_player(mut commands: Commands, player_query: Query<Entity, With<Player>>) {
+    if let Ok(player_entity) = player_query.single() {
+        commands.entity(player_entity).insert(Outlined);
+    }
+}
+
+fn update_mask_camera(
+    mut images: ResMut<Assets<Image>>,
+    main_camera_query: Query<(&Projection, &OutlineMask), With<MainCamera>>,
+    mut mask_camera_query: Query<&mut Projection, (With<OutlineMaskCamera>, Without<MainCamera>)>,
+    window_query: Query<&Window, With<PrimaryWindow>>,
+) {
+    if let Ok((projection, mask)) = main_camera_query.single()
+        && let Ok(mut mask_projection) = mask_camera_query.single_mut()
+        && let Ok(window) = window_query.single()
+    {
+        *mask_projection = projection.clone();
+        let size = window.physical_size().max(UVec2::ONE);
+        if let Some(image) = images.get(&mask.0)
+            && image.size() != size
+            && let Some(image) = images.get_mut(&mask.0)
+        {
+            image.resize(Extent3d {
+                width: size.x,
+                height: size.y,
+                ..default()
+            });
+        }
+    }
+}
+
+// Only `OUTLINE_LAYER` is added and taken away, so the entity keeps any layers it already had.
+fn update_outline_layers(
+    mut commands: Commands,
+    outlined_query: Query<(Entity, Option<&RenderLayers>), Added<Outlined>>,
+    layers_query: Query<&RenderLayers, Without<Outlined>>,
+    mut removed: RemovedComponents<Outlined>,
+) {
+    for (entity, layers) in outlined_query.iter() {
+        let layers = layers.cloned().unwrap_or_default().with(OUTLINE_LAYER);
+        commands.entity(entity).try_insert(layers);
+    }
+    for entity in removed.read() {
+        if let Ok(layers) = layers_query.get(entity)
+            && let Ok(mut entity_commands) = commands.get_entity(entity)
+        {
+            let layers = layers.clone().without(OUTLINE_LAYER);
+            if layers == RenderLayers::default() {
+                entity_commands.remove::<RenderLayers>();
+            } else {
+                entity_commands.try_insert(layers);
+            }
+        }
     }
 }
 
 impl Plugin for OutlinePlugin {
     fn build(&self, app: &mut App) {
         app.add_plugins((
-            ExtractComponentPlugin::<OutlineSettings>::default(),
-            UniformComponentPlugin::<OutlineSettings>::default(),
+            PostProcessPlugin::<OutlineSettings>::default(),
+            ExtractComponentPlugin::<OutlineMask>::default(),
         ))
-        .add_systems(Startup, add_components_main_camera);
-        let Some(render_app) = app.get_sub_app_mut(RenderApp) else {
-            return;
-        };
-        render_app
-            .add_render_graph_node::<ViewNodeRunner<OutlineNode>>(Core3d, OutlineLabel)
-            .add_render_graph_edges(
-                Core3d,
-                (
-                    Node3d::Tonemapping,
-                    OutlineLabel,
-                    Node3d::EndMainPassPostProcessing,
-                ),
-            );
-    }
-
-    fn finish(&self, app: &mut App) {
-        let Some(render_app) = app.get_sub_app_mut(RenderApp) else {
-            return;
-        };
-
-        render_app.init_resource::<OutlinePipeline>();
+        .add_systems(Startup, (add_components_main_camera, add_components_player))
+        .add_systems(Update, (update_mask_camera, update_outline_layers));
     }
 }
 
-#[derive(Component, Default, Clone, Copy, ExtractComponent, ShaderType)]
+#[derive(Component, Clone, Copy, ExtractComponent, ShaderType)]
 pub struct OutlineSettings {
+    pub color: LinearRgba,
+    /// Distance in pixels between the samples compared to find an edge.
+    pub thickness: f32,
+    /// Relative depth difference inside an outlined entity that counts as an edge.
+    pub depth_threshold: f32,
+    /// Normal difference inside an outlined entity that counts as an edge.
+    pub normal_threshold: f32,
+    /// Mask coverage above which a pixel belongs to an outlined entity.
     pub cutoff: f32,
 }
 
-#[derive(Debug, Hash, PartialEq, Eq, Clone, RenderLabel)]
-struct OutlineLabel;
-
-#[derive(Default)]
-struct OutlineNode;
-
-impl ViewNode for OutlineNode {
-    type ViewQuery = (
-        &'static ViewTarget,
-        &'static OutlineSettings,
-        &'static DynamicUniformIndex<OutlineSettings>,
-        &'static ViewPrepassTextures,
-    );
-
-    fn run(
-        &self,
-        _graph: &mut RenderGraphContext,
-        render_context: &mut RenderContext,
-        (view_target, _post_process_settings, settings_index, view_prepass_textures): QueryItem<
-            Self::ViewQuery,
-        >,
-        world: &World,
-    ) -> Result<(), NodeRunError> {
-        let post_process_pipeline = world.resource::<OutlinePipeline>();
-        let pipeline_cache = world.resource::<PipelineCache>();
-
-        let Some(pipeline) = pipeline_cache.get_render_pipeline(post_process_pipeline.pipeline_id)
-        else {
-            return Ok(());
-        };
-
-        let settings_uniforms = world.resource::<ComponentUniforms<OutlineSettings>>();
-        let Some(settings_binding) = settings_uniforms.uniforms().binding() else {
-            return Ok(());
-        };
-
-        let post_process = view_target.post_process_write();
-        let depth_view = view_prepass_textures.depth_view().expect("no depth view");
-        let normals_view = view_prepass_textures
-            .normal_view()
-            .expect("no normals view");
-        let bind_group = render_context.render_device().create_bind_group(
-            "post_process_bind_group",
-            &post_process_pipeline.layout,
-            &BindGroupEntries::sequential((
-                post_process.source,
-                &post_process_pipeline.color_sampler,
-                depth_view,
-                normals_view,
-                settings_binding.clone(),
-            )),
-        );
-
-        let mut render_pass = render_context.begin_tracked_render_pass(RenderPassDescriptor {
-            label: Some("post_process_pass"),
-            color_attachments: &[Some(RenderPassColorAttachment {
-                view: post_process.destination,
-                resolve_target: None,
-                ops: Operations::default(),
-                depth_slice: None,
-            })],
-            depth_stencil_attachment: None,
-            timestamp_writes: None,
-            occlusion_query_set: None,
-        });
-
-        render_pass.set_render_pipeline(pipeline);
-        render_pass.set_bind_group(0, &bind_group, &[settings_index.index()]);
-        render_pass.draw(0..3, 0..1);
-
-        Ok(())
+impl Default for OutlineSettings {
+    fn default() -> Self {
+        Self {
+            color: LinearRgba::new(1.0, 0.8, 0.2, 1.0),
+            thickness: 2.0,
+            depth_threshold: 0.05,
+            normal_threshold: 0.6,
+            cutoff: 0.5,
+        }
     }
 }
 
-impl FromWorld for OutlinePipeline {
-    fn from_world(world: &mut World) -> Self {
-        let render_device = world.resource::<RenderDevice>();
-        let layout = render_device.create_bind_group_layout(
-            "post_process_bind_group_layout",
-            &BindGroupLayoutEntries::sequential(
-                ShaderStages::FRAGMENT,
-                (
-                    texture_2d(TextureSampleType::Float { filterable: true }),
-                    sampler(SamplerBindingType::Filtering),
-                    texture_depth_2d_multisampled(),
-                    texture_2d_multisampled(TextureSampleType::Float { filterable: false }),
-                    uniform_buffer::<OutlineSettings>(true),
-                ),
-            ),
-        );
-
-        let color_sampler = render_device.create_sampler(&SamplerDescriptor::default());
-        let shader = world.load_asset("shaders/outline.wgsl");
-        let vertex_state = world.resource::<FullscreenShader>().to_vertex_state();
-        let pipeline_id =
-            world
-                .resource_mut::<PipelineCache>()
-                .queue_render_pipeline(RenderPipelineDescriptor {
-                    label: Some("outline_pipline".into()),
-                    layout: vec![layout.clone()],
-                    vertex: vertex_state,
-                    fragment: Some(FragmentState {
-                        shader,
-                        shader_defs: vec![],
-                        entry_point: Some("fragment".into()),
-                        targets: vec![Some(ColorTargetState {
-                            format: TextureFormat::bevy_default(),
-                            blend: None,
-                            write_mask: ColorWrites::ALL,
-                        })],
-                    }),
-                    primitive: PrimitiveState::default(),
-                    depth_stencil: None,
-                    multisample: MultisampleState::default(),
-                    push_constant_ranges: vec![],
-                    zero_initialize_workgroup_memory: true,
-                });
+impl PostProcessSettings for OutlineSettings {
+    const SHADER: &'static str = "shaders/outline.wgsl";
+    const STAGE: PostProcessStage = PostProcessStage::Image;
+    // Before chromatic abberation, so the outline is split into color fringes like the rest.
+    const ORDER: i32 = 0;
+    type Label = OutlineLabel;
+    type ViewQuery = (&'static ViewPrepassTextures, &'static OutlineMask);
+
+    fn extra_layout_entries() -> Vec<BindGroupLayoutEntryBuilder> {
+        vec![
+            texture_depth_2d_multisampled(),
+            texture_2d_multisampled(TextureSampleType::Float { filterable: false }),
+            texture_2d(TextureSampleType::Float { filterable: true }),
+        ]
+    }
 
-        Self {
-            layout,
-            color_sampler,
-            pipeline_id,
-        }
+    fn extra_bindings<'w>(
+        (view_prepass_textures, mask): QueryItem<'w, '_, Self::ViewQuery>,
+        world: &'w World,
+    ) -> Option<Vec<BindingResource<'w>>> {
+        let mask = world.resource::<RenderAssets<GpuImage>>().get(&mask.0)?;
+        Some(vec![
+            view_prepass_textures.depth_view()?.into_binding(),
+            view_prepass_textures.normal_view()?.into_binding(),
+            mask.texture_view.into_binding(),
+        ])
     }
 }
 
-#[derive(Resource)]
-struct OutlinePipeline {
-    layout: BindGroupLayout,
-    color_sampler: Sampler,
-    pipeline_id: CachedRenderPipelineId,
-}
+#[derive(Debug, Hash, PartialEq, Eq, Clone, Default, RenderLabel)]
+pub struct OutlineLabel;
@@ -0,0 +1,333 @@
+use avian3d::prelude::LinearVelocity;
+use bevy::{light::NotShadowCaster, platform::collections::HashMap, prelude::*};
+use rand::{rngs::StdRng, Rng};
+
+use crate::{
+    core::{
+        damage::Destroyed,
+        particles::{Particle, ParticleEffect, ParticleEmitter, ParticleShape, SpawnParticles},
+        player::{Player, ENGINE_OFFSET},
+        weapon::WeaponFired,
+    },
+    plugins::ron_asset::RonAssetPlugin,
+    resources::{particle_effects::ParticleEffects, world_seed::WorldSeed},
+};
+
+pub struct ParticlesPlugin;
+
+const MAX_PARTICLES: usize = 4096;
+/// Number of materials each effect's color gradient is split into, so particles of an effect share
+/// a handful of materials and are drawn in batches.
+const COLOR_STEPS: usize = 8;
+/// Extra spark length per unit of speed.
+const SPARK_STRETCH: f32 = 0.04;
+
+impl Plugin for ParticlesPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugins(RonAssetPlugin::<ParticleEffect>::new(&["particle.ron"]))
+            .add_message::<SpawnParticles>()
+            .init_resource::<ParticleEffects>()
+            .init_resource::<ParticleMaterials>()
+            .init_resource::<ParticleRng>()
+            .add_systems(Startup, add_components_player)
+            .add_systems(
+                Update,
+                (
+                    reload_particle_materials,
+                    spawn_muzzle_flashes,
+                    spawn_destruction_particles,
+                    spawn_particle_bursts,
+                    follow_emitter_velocity,
+                    update_emitters,
+                    update_particles,
+                )
+                    .chain(),
+            );
+    }
+}
+
+#[derive(Resource)]
+struct ParticleMaterials {
+    meshes: HashMap<ParticleShape, Handle<Mesh>>,
+    /// Materials along each effect's color gradient, created when the effect is first emitted.
+    materials: HashMap<AssetId<ParticleEffect>, Vec<Handle<StandardMaterial>>>,
+}
+
+impl FromWorld for ParticleMaterials {
+    fn from_world(world: &mut World) -> Self {
+        let mut meshes = world.resource_mut::<Assets<Mesh>>();
+        let orb = meshes.add(Sphere::new(0.5).mesh().ico(1).unwrap());
+        let debris = meshes.add(Tetrahedron::default());
+        Self {
+            meshes: HashMap::from_iter([
+                (ParticleShape::Orb, orb.clone()),
+                (ParticleShape::Spark, orb),
+                (ParticleShape::Debris, debris),
+            ]),
+            materials: HashMap::default(),
+        }
+    }
+}
+
+/// Random stream of the particle systems, derived from the world seed.
+#[derive(Resource)]
+struct ParticleRng(StdRng);
+
+impl FromWorld for ParticleRng {
+    fn from_world(world: &mut World) -> Self {
+        Self(world.resource::<WorldSeed>().rng("particles"))
+    }
+}
+
+impl ParticleMaterials {
+    fn get_or_create(
+        &mut self,
+        id: AssetId<ParticleEffect>,
+        effect: &ParticleEffect,
+        materials: &mut Assets<StandardMaterial>,
+    ) -> &[Handle<StandardMaterial>] {
+        self.materials.entry(id).or_insert_with(|| {
+            (0..COLOR_STEPS)
+                .map(|step| {
+                    let color = effect
+                        .start_color
+                        .mix(&effect.end_color, step as f32 / (COLOR_STEPS - 1) as f32);
+                    materials.add(match effect.shape {
+                        ParticleShape::Orb | ParticleShape::Spark => StandardMaterial {
+                            base_color: color.into(),
+                            unlit: true,
+                            alpha_mode: AlphaMode::Add,
+                            ..default()
+                        },
+                        ParticleShape::Debris => StandardMaterial {
+                            base_color: color.into(),
+                            perceptual_roughness: 0.9,
+                            ..default()
+                        },
+                    })
+                })
+                .collect()
+        })
+    }
+}
+
+fn add_components_player(
+    mut commands: Commands,
+    particle_effects: Res<ParticleEffects>,
+    player_query: Query<Entity, With<Player>>,
+) {
+    if let Ok(player_entity) = player_query.single() {
+        commands.spawn((
+            ParticleEmitter::new(particle_effects.engine_exhaust.clone()),
+            Transform::from_translation(ENGINE_OFFSET).looking_to(Vec3::Z, Vec3::Y),
+            ChildOf(player_entity),
+        ));
+    }
+}
+
+// Rebuilds the materials of effects edited while the game runs.
+fn reload_particle_materials(
+    mut asset_events: MessageReader<AssetEvent<ParticleEffect>>,
+    mut particle_materials: ResMut<ParticleMaterials>,
+) {
+    for event in asset_events.read() {
+        if let AssetEvent::Modified { id } = event {
+            particle_materials.materials.remove(id);
+        }
+    }
+}
+
+fn spawn_particle_bursts(
+    mut commands: Commands,
+    mut spawn_particles: MessageReader<SpawnParticles>,
+) {
+    for SpawnParticles {
+        effect,
+        transform,
+        velocity,
+    } in spawn_particles.read()
+    {
+        commands.spawn((
+            ParticleEmitter {
+                velocity: *velocity,
+                ..ParticleEmitter::new(effect.clone())
+            },
+            *transform,
+            // Emitted from right away, before transform propagation catches up.
+            GlobalTransform::from(*transform),
+        ));
+    }
+}
+
+fn spawn_muzzle_flashes(
+    mut fired: MessageReader<WeaponFired>,
+    mut spawn_particles: MessageWriter<SpawnParticles>,
+    particle_effects: Res<ParticleEffects>,
+    velocity_query: Query<&LinearVelocity>,
+) {
+    for WeaponFired { shooter, hardpoint } in fired.read() {
+        spawn_particles.write(SpawnParticles {
+            effect: particle_effects.muzzle_flash.clone(),
+            transform: *hardpoint,
+            velocity: velocity_query
+                .get(*shooter)
+                .map_or(Vec3::ZERO, |velocity| velocity.0),
+        });
+    }
+}
+
+fn spawn_destruction_particles(
+    mut destroyed: MessageReader<Destroyed>,
+    mut spawn_particles: MessageWriter<SpawnParticles>,
+    particle_effects: Res<ParticleEffects>,
+) {
+    for Destroyed {
+        transform,
+        velocity,
+    } in destroyed.read()
+    {
+        for effect in [&particle_effects.fireball, &particle_effects.debris] {
+            spawn_particles.write(SpawnParticles {
+                effect: effect.clone(),
+                transform: *transform,
+                velocity: *velocity,
+            });
+        }
+    }
+}
+
+// Emitters attached to a physics body pass the body's velocity on to their particles.
+fn follow_emitter_velocity(
+    mut emitter_query: Query<(&mut ParticleEmitter, &ChildOf)>,
+    velocity_query: Query<&LinearVelocity>,
+) {
+    for (mut emitter, child_of) in &mut emitter_query {
+        if let Ok(velocity) = velocity_query.get(child_of.parent()) {
+            emitter.velocity = velocity.0;
+        }
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn update_emitters(
+    mut commands: Commands,
+    time: Res<Time>,
+    effects: Res<Assets<ParticleEffect>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut particle_materials: ResMut<ParticleMaterials>,
+    mut emitter_query: Query<(Entity, &mut ParticleEmitter, &GlobalTransform)>,
+    particle_query: Query<(), With<Particle>>,
+    mut rng: ResMut<ParticleRng>,
+) {
+    let rng = &mut rng.0;
+    let mut budget = MAX_PARTICLES.saturating_sub(particle_query.iter().count());
+    for (emitter_entity, mut emitter, global_transform) in &mut emitter_query {
+        let Some(effect) = effects.get(&emitter.effect) else {
+            continue;
+        };
+
+        emitter.pending += effect.rate * emitter.intensity * time.delta_secs();
+        let mut count = emitter.pending.floor() as usize;
+        emitter.pending -= count as f32;
+        if !emitter.burst_emitted {
+            emitter.burst_emitted = true;
+            count += effect.burst as usize;
+        }
+        let count = count.min(budget);
+        budget -= count;
+
+        if count > 0 {
+            let mesh = particle_materials.meshes[&effect.shape].clone();
+            let material =
+                particle_materials.get_or_create(emitter.effect.id(), effect, &mut materials)[0]
+                    .clone();
+            let (_, rotation, position) = global_transform.to_scale_rotation_translation();
+            for _ in 0..count {
+                let direction = rotation * random_cone_direction(effect.spread, rng);
+                let axis = Vec3::new(rng.r#gen(), rng.r#gen(), rng.r#gen()) * 2.0 - 1.0;
+                let mut particle = commands.spawn((
+                    Particle {
+                        effect: emitter.effect.id(),
+                        velocity: direction * random_in(effect.speed, rng)
+                            + emitter.velocity * effect.inherit_velocity,
+                        angular_velocity: axis.normalize_or_zero() * effect.angular_speed,
+                        age: 0.0,
+                        lifetime: random_in(effect.lifetime, rng),
+                        color_step: 0,
+                    },
+                    Mesh3d(mesh.clone()),
+                    MeshMaterial3d(material.clone()),
+                    Transform::from_translation(position).with_scale(Vec3::splat(effect.size.x)),
+                ));
+                if effect.shape != ParticleShape::Debris {
+                    particle.insert(NotShadowCaster);
+                }
+            }
+        }
+
+        emitter.elapsed += time.delta_secs();
+        if effect.duration > 0.0 && emitter.elapsed >= effect.duration {
+            commands.entity(emitter_entity).despawn();
+        }
+    }
+}
+
+fn update_particles(
+    mut commands: Commands,
+    time: Res<Time>,
+    effects: Res<Assets<ParticleEffect>>,
+    particle_materials: Res<ParticleMaterials>,
+    mut particle_query: Query<(
+        Entity,
+        &mut Particle,
+        &mut Transform,
+        &mut MeshMaterial3d<StandardMaterial>,
+    )>,
+) {
+    let delta_secs = time.delta_secs();
+    for (particle_entity, mut particle, mut transform, mut material) in &mut particle_query {
+        particle.age += delta_secs;
+        let effect = match effects.get(particle.effect) {
+            Some(effect) if particle.age < particle.lifetime => effect,
+            _ => {
+                commands.entity(particle_entity).despawn();
+                continue;
+            }
+        };
+
+        particle.velocity /= 1.0 + effect.drag * delta_secs;
+        transform.translation += particle.velocity * delta_secs;
+        let t = particle.age / particle.lifetime;
+        let size = effect.size.x.lerp(effect.size.y, t);
+        if effect.shape == ParticleShape::Spark {
+            let length = size + particle.velocity.length() * SPARK_STRETCH;
+            transform.look_to(particle.velocity, Vec3::Y);
+            transform.scale = Vec3::new(size, size, length);
+        } else {
+            transform.rotate(Quat::from_scaled_axis(
+                particle.angular_velocity * delta_secs,
+            ));
+            transform.scale = Vec3::splat(size);
+        }
+
+        let color_step = ((t * COLOR_STEPS as f32) as usize).min(COLOR_STEPS - 1);
+        if color_step != particle.color_step
+            && let Some(handles) = particle_materials.materials.get(&particle.effect)
+        {
+            material.0 = handles[color_step].clone();
+            particle.color_step = color_step;
+        }
+    }
+}
+
+/// A uniformly distributed direction within `spread` radians of the forward axis.
+fn random_cone_direction(spread: f32, rng: &mut impl Rng) -> Vec3 {
+    let cos_theta = 1.0 - rng.r#gen::<f32>() * (1.0 - spread.cos());
+    let sin_theta = (1.0 - cos_theta * cos_theta).max(0.0).sqrt();
+    let phi = rng.gen_range(0.0..std::f32::consts::TAU);
+    Vec3::new(sin_theta * phi.cos(), sin_theta * phi.sin(), -cos_theta)
+}
+
+fn random_in(range: Vec2, rng: &mut impl Rng) -> f32 {
+    range.x.lerp(range.y, rng.r#gen())
+}
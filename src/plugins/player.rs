@@ -2,9 +2,13 @@ use avian3d::prelude::{
     AngularDamping, AngularVelocity, Collider, LinearDamping, LinearVelocity, RigidBody,
     TransformInterpolation,
 };
-use bevy::prelude::*;
+use bevy::{light::NotShadowCaster, prelude::*};
 
-use crate::core::player::Player;
+use crate::core::{
+    asteroid::Cargo,
+    player::{Player, ENGINE_OFFSET},
+    stats::{Gauge, Health, Shield},
+};
 
 pub struct PlayerPlugin;
 
@@ -16,9 +20,21 @@ fn add_components_player(
     mut commands: Commands,
     player_query: Query<Entity, With<Player>>,
     asset_server: Res<AssetServer>,
+    mut meshes: ResMut<Assets<Mesh>>,
     mut materials: ResMut<Assets<StandardMaterial>>,
 ) {
     if let Ok(player_entity) = player_query.single() {
+        commands.spawn((
+            Mesh3d(meshes.add(Sphere::new(0.2))),
+            MeshMaterial3d(materials.add(StandardMaterial {
+                base_color: Color::BLACK,
+                emissive: LinearRgba::rgb(2.0, 6.0, 16.0),
+                ..default()
+            })),
+            Transform::from_translation(ENGINE_OFFSET).with_scale(Vec3::new(1.0, 1.0, 0.4)),
+            NotShadowCaster,
+            ChildOf(player_entity),
+        ));
         commands.entity(player_entity).insert((
             Mesh3d(
                 asset_server.load(
@@ -40,6 +56,13 @@ fn add_components_player(
             AngularDamping(0.9),
             RigidBody::Dynamic,
             Collider::sphere(0.5),
+            Health {
+                value: Gauge::new(100.0),
+            },
+            Shield {
+                value: Gauge::new(50.0),
+            },
+            Cargo::default(),
         ));
     }
 }
@@ -1,10 +1,12 @@
-use avian3d::{
-    math::{Vector, Vector3},
-    prelude::LinearVelocity,
-};
+use avian3d::prelude::{AngularVelocity, LinearVelocity};
 use bevy::{input::mouse::MouseMotion, prelude::*};
 
-use crate::core::player::Player;
+use crate::core::{
+    chromatic_abberation::AbberationPulse,
+    damage::Invulnerable,
+    main_camera::{CameraMode, MainCamera},
+    player::Player,
+};
 
 use super::weapon::{WeaponSlotType, WeaponSlots};
 
@@ -14,6 +16,7 @@ impl Plugin for PlayerControllerPlugin {
     fn build(&self, app: &mut App) {
         app.add_systems(Startup, add_player_controller)
             .add_systems(FixedUpdate, player_movement)
+            .add_systems(FixedUpdate, player_manoeuvres)
             .add_systems(Update, handle_player_input)
             .add_systems(Update, player_weapon_fire);
     }
@@ -26,6 +29,7 @@ struct PlayerController {
     pub max_movement_speed: f32,
     pub max_rotation_speed: f32,
     pub movement_force_strength: f32,
+    pub manoeuvre_input: Option<ManoeuvreInput>,
 }
 
 impl PlayerController {
@@ -39,11 +43,77 @@ impl PlayerController {
     }
 }
 
+#[derive(Debug, Clone, Copy)]
+enum ManoeuvreInput {
+    Dash(f32),
+    Roll(f32),
+}
+
+#[derive(Component, Debug)]
+struct Manoeuvres {
+    pub dash: Manoeuvre,
+    pub roll: Manoeuvre,
+    pub dash_impulse: f32,
+    pub dash_abberation: f32,
+    pub roll_impulse: f32,
+    pub roll_duration: f32,
+    pub roll_invulnerability: f32,
+    rolling: Option<Roll>,
+}
+
+#[derive(Debug)]
+struct Roll {
+    direction: f32,
+    remaining: f32,
+}
+
+impl Manoeuvres {
+    pub fn new() -> Self {
+        Self {
+            dash: Manoeuvre::new(1.0),
+            roll: Manoeuvre::new(2.5),
+            dash_impulse: 30.0,
+            dash_abberation: 0.03,
+            roll_impulse: 15.0,
+            roll_duration: 0.5,
+            roll_invulnerability: 0.5,
+            rolling: None,
+        }
+    }
+}
+
+#[derive(Debug)]
+struct Manoeuvre {
+    cooldown: f32,
+    remaining_cooldown: f32,
+}
+
+impl Manoeuvre {
+    fn new(cooldown: f32) -> Self {
+        Self {
+            cooldown,
+            remaining_cooldown: 0.0,
+        }
+    }
+
+    fn tick(&mut self, delta_secs: f32) {
+        self.remaining_cooldown = (self.remaining_cooldown - delta_secs).max(0.0);
+    }
+
+    fn trigger(&mut self) -> bool {
+        if self.remaining_cooldown > 0.0 {
+            return false;
+        }
+        self.remaining_cooldown = self.cooldown;
+        true
+    }
+}
+
 fn add_player_controller(mut commands: Commands, player_query: Query<Entity, With<Player>>) {
     if let Ok(player_entity) = player_query.single() {
         commands
             .entity(player_entity)
-            .insert(PlayerController::new());
+            .insert((PlayerController::new(), Manoeuvres::new()));
     }
 }
 
@@ -51,8 +121,12 @@ fn handle_player_input(
     keyboard_input: Res<ButtonInput<KeyCode>>,
     mut mouse_movement: MessageReader<MouseMotion>,
     mut player_query: Query<&mut PlayerController, With<Player>>,
+    camera_query: Query<&CameraMode, With<MainCamera>>,
 ) {
     if let Ok(mut player_controller) = player_query.single_mut() {
+        let orbiting = camera_query
+            .single()
+            .is_ok_and(|camera_mode| camera_mode.is_orbiting(&keyboard_input));
         let mut movement_input = Vec3::ZERO;
         let mut rotation_input = Vec3::ZERO;
         if keyboard_input.any_pressed([KeyCode::KeyW]) {
@@ -64,10 +138,21 @@ fn handle_player_input(
         if keyboard_input.any_pressed([KeyCode::KeyD]) {
             rotation_input += Vec3::Z;
         }
-        for event in mouse_movement.read() {
+        // While orbiting, the camera takes the mouse.
+        for event in mouse_movement.read().filter(|_| !orbiting) {
             let clamped_mouse = event.delta.clamp(Vec2::NEG_ONE, Vec2::ONE);
             rotation_input -= Vec3::new(clamped_mouse.x, clamped_mouse.y, 0.0);
         }
+        if keyboard_input.just_pressed(KeyCode::KeyQ) {
+            player_controller.manoeuvre_input = Some(ManoeuvreInput::Dash(-1.0));
+        }
+        if keyboard_input.just_pressed(KeyCode::KeyE) {
+            player_controller.manoeuvre_input = Some(ManoeuvreInput::Dash(1.0));
+        }
+        if keyboard_input.just_pressed(KeyCode::Space) {
+            let direction = if rotation_input.z < 0.0 { -1.0 } else { 1.0 };
+            player_controller.manoeuvre_input = Some(ManoeuvreInput::Roll(direction));
+        }
         player_controller.movement_input = movement_input;
         player_controller.rotation_input = rotation_input;
     }
@@ -96,6 +181,67 @@ fn player_movement(
     }
 }
 
+fn player_manoeuvres(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut abberation: MessageWriter<AbberationPulse>,
+    mut player_query: Query<(
+        Entity,
+        &Transform,
+        &mut PlayerController,
+        &mut Manoeuvres,
+        &mut LinearVelocity,
+        &mut AngularVelocity,
+    )>,
+) {
+    if let Ok((
+        player_entity,
+        transform,
+        mut player_controller,
+        mut manoeuvres,
+        mut linear_velocity,
+        mut angular_velocity,
+    )) = player_query.single_mut()
+    {
+        manoeuvres.dash.tick(time.delta_secs());
+        manoeuvres.roll.tick(time.delta_secs());
+        let manoeuvre_input = player_controller.manoeuvre_input.take();
+        if let Some(ManoeuvreInput::Dash(direction)) = manoeuvre_input
+            && manoeuvres.dash.trigger()
+        {
+            linear_velocity.0 += transform.right() * direction * manoeuvres.dash_impulse;
+            abberation.write(AbberationPulse(manoeuvres.dash_abberation));
+        }
+        if let Some(ManoeuvreInput::Roll(direction)) = manoeuvre_input
+            && manoeuvres.rolling.is_none()
+            && manoeuvres.roll.trigger()
+        {
+            linear_velocity.0 += transform.right() * direction * manoeuvres.roll_impulse;
+            manoeuvres.rolling = Some(Roll {
+                direction,
+                remaining: manoeuvres.roll_duration,
+            });
+            commands.entity(player_entity).insert(Invulnerable {
+                remaining: manoeuvres.roll_invulnerability,
+            });
+        }
+
+        // Drive the roll axis directly so the ship completes exactly one turn.
+        let roll_axis = transform.forward().as_vec3();
+        let roll_speed = std::f32::consts::TAU / manoeuvres.roll_duration;
+        if let Some(roll) = &mut manoeuvres.rolling {
+            let current = angular_velocity.0.dot(roll_axis);
+            angular_velocity.0 += roll_axis * (roll.direction * roll_speed - current);
+            roll.remaining -= time.delta_secs();
+            if roll.remaining <= 0.0 {
+                let current = angular_velocity.0.dot(roll_axis);
+                angular_velocity.0 -= roll_axis * current;
+                manoeuvres.rolling = None;
+            }
+        }
+    }
+}
+
 fn player_weapon_fire(
     mouse_button_input: Res<ButtonInput<MouseButton>>,
     mut weapon_slots_query: Query<&mut WeaponSlots, With<Player>>,
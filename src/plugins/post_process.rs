@@ -0,0 +1,380 @@
+use std::marker::PhantomData;
+
+use bevy::{
+    core_pipeline::{
+        core_3d::graph::{Core3d, Node3d},
+        FullscreenShader,
+    },
+    ecs::query::{QueryItem, ReadOnlyQueryData},
+    prelude::*,
+    render::{
+        extract_component::{
+            ComponentUniforms, DynamicUniformIndex, ExtractComponent, ExtractComponentPlugin,
+            UniformComponentPlugin,
+        },
+        render_graph::{
+            InternedRenderLabel, NodeRunError, RenderGraphContext, RenderGraphExt, RenderLabel,
+            ViewNode, ViewNodeRunner,
+        },
+        render_resource::{
+            binding_types::{sampler, texture_2d, uniform_buffer},
+            encase::internal::WriteInto,
+            BindGroup, BindGroupEntry, BindGroupLayout, BindGroupLayoutEntry,
+            BindGroupLayoutEntryBuilder, BindingResource, CachedRenderPipelineId, ColorTargetState,
+            ColorWrites, FragmentState, IntoBinding, MultisampleState, Operations, PipelineCache,
+            PrimitiveState, RenderPassColorAttachment, RenderPassDescriptor, RenderPipeline,
+            RenderPipelineDescriptor, Sampler, SamplerBindingType, SamplerDescriptor, ShaderStages,
+            ShaderType, SpecializedRenderPipeline, SpecializedRenderPipelines, TextureFormat,
+            TextureSampleType, TextureView,
+        },
+        renderer::{RenderContext, RenderDevice},
+        view::ViewTarget,
+        Render, RenderApp, RenderSystems,
+    },
+};
+
+/// Where in the render graph an effect runs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PostProcessStage {
+    /// On the HDR scene, between the main pass and bloom, so bright results bloom and are
+    /// tonemapped along with the rest of the scene.
+    Scene,
+    /// On the final image, between tonemapping and the end of post processing.
+    Image,
+}
+
+impl PostProcessStage {
+    const ALL: [PostProcessStage; 2] = [PostProcessStage::Scene, PostProcessStage::Image];
+
+    fn bounds(self) -> (Node3d, Node3d) {
+        match self {
+            PostProcessStage::Scene => (Node3d::EndMainPass, Node3d::StartMainPassPostProcessing),
+            PostProcessStage::Image => (Node3d::Tonemapping, Node3d::EndMainPassPostProcessing),
+        }
+    }
+}
+
+/// Every effect's render graph node, with the stage and order it runs in.
+#[derive(Resource, Default)]
+struct PostProcessEffects(Vec<(PostProcessStage, i32, InternedRenderLabel)>);
+
+/// Adds the node `label` to the post-process chain. Within a stage, effects with a lower `order`
+/// run first. Effects with their own node call this from `build`; `PostProcessPlugin` does it for
+/// `PostProcessSettings` effects.
+pub fn register_post_process_effect(
+    app: &mut App,
+    stage: PostProcessStage,
+    order: i32,
+    label: impl RenderLabel,
+) {
+    app.world_mut()
+        .get_resource_or_init::<PostProcessEffects>()
+        .0
+        .push((stage, order, label.intern()));
+}
+
+/// Connects the registered post-process effects in order within their stages.
+pub struct PostProcessChainPlugin;
+
+impl Plugin for PostProcessChainPlugin {
+    fn build(&self, _app: &mut App) {}
+
+    // Edges are added once every effect plugin has added its node.
+    fn finish(&self, app: &mut App) {
+        let mut effects = app
+            .world_mut()
+            .remove_resource::<PostProcessEffects>()
+            .unwrap_or_default()
+            .0;
+        effects.sort_by_key(|(_, order, _)| *order);
+        let Some(render_app) = app.get_sub_app_mut(RenderApp) else {
+            return;
+        };
+        for stage in PostProcessStage::ALL {
+            let (start, end) = stage.bounds();
+            let mut previous = start.intern();
+            for (_, _, label) in effects
+                .iter()
+                .filter(|(effect_stage, ..)| *effect_stage == stage)
+            {
+                render_app.add_render_graph_edge(Core3d, previous, *label);
+                previous = *label;
+            }
+            render_app.add_render_graph_edge(Core3d, previous, end);
+        }
+    }
+}
+
+/// Settings of a single pass, full screen post-process effect.
+///
+/// The shader's `fragment` entry point reads the source image at binding 0, a filtering sampler
+/// at binding 1 and these settings at binding 2. Extra bindings follow from binding 3. The
+/// pipeline is specialized for each view's target format, so the effect runs on HDR targets too.
+pub trait PostProcessSettings: ExtractComponent + ShaderType + WriteInto + Clone {
+    const SHADER: &'static str;
+    const STAGE: PostProcessStage;
+    /// Position within the stage, lower runs first.
+    const ORDER: i32;
+    type Label: RenderLabel + Default;
+    /// Additional view data needed to fill the extra bindings.
+    type ViewQuery: ReadOnlyQueryData;
+
+    fn extra_layout_entries() -> Vec<BindGroupLayoutEntryBuilder> {
+        Vec::new()
+    }
+
+    /// Resources for the extra bindings, or `None` to skip the effect this frame.
+    fn extra_bindings<'w>(
+        view: QueryItem<'w, '_, Self::ViewQuery>,
+        world: &'w World,
+    ) -> Option<Vec<BindingResource<'w>>>;
+}
+
+pub struct PostProcessPlugin<S: PostProcessSettings>(PhantomData<S>);
+
+impl<S: PostProcessSettings> Default for PostProcessPlugin<S> {
+    fn default() -> Self {
+        Self(PhantomData)
+    }
+}
+
+impl<S: PostProcessSettings> Plugin for PostProcessPlugin<S> {
+    fn build(&self, app: &mut App) {
+        app.add_plugins((
+            ExtractComponentPlugin::<S>::default(),
+            UniformComponentPlugin::<S>::default(),
+        ));
+        let Some(render_app) = app.get_sub_app_mut(RenderApp) else {
+            return;
+        };
+        render_app
+            .init_resource::<SpecializedRenderPipelines<PostProcessPipeline<S>>>()
+            .add_systems(
+                Render,
+                prepare_post_process_pipelines::<S>.in_set(RenderSystems::Prepare),
+            )
+            .add_render_graph_node::<ViewNodeRunner<PostProcessNode<S>>>(
+                Core3d,
+                S::Label::default(),
+            );
+        register_post_process_effect(app, S::STAGE, S::ORDER, S::Label::default());
+    }
+
+    fn finish(&self, app: &mut App) {
+        let Some(render_app) = app.get_sub_app_mut(RenderApp) else {
+            return;
+        };
+
+        render_app.init_resource::<PostProcessPipeline<S>>();
+    }
+}
+
+#[derive(Component)]
+struct ViewPostProcessPipeline<S> {
+    id: CachedRenderPipelineId,
+    marker: PhantomData<S>,
+}
+
+fn prepare_post_process_pipelines<S: PostProcessSettings>(
+    mut commands: Commands,
+    pipeline_cache: Res<PipelineCache>,
+    mut pipelines: ResMut<SpecializedRenderPipelines<PostProcessPipeline<S>>>,
+    post_process_pipeline: Res<PostProcessPipeline<S>>,
+    view_query: Query<(Entity, &ViewTarget), With<S>>,
+) {
+    for (entity, view_target) in &view_query {
+        let id = pipelines.specialize(
+            &pipeline_cache,
+            &post_process_pipeline,
+            view_target.main_texture_format(),
+        );
+        commands
+            .entity(entity)
+            .insert(ViewPostProcessPipeline::<S> {
+                id,
+                marker: PhantomData,
+            });
+    }
+}
+
+struct PostProcessNode<S>(PhantomData<S>);
+
+impl<S> Default for PostProcessNode<S> {
+    fn default() -> Self {
+        Self(PhantomData)
+    }
+}
+
+impl<S: PostProcessSettings> ViewNode for PostProcessNode<S> {
+    type ViewQuery = (
+        &'static ViewTarget,
+        &'static DynamicUniformIndex<S>,
+        &'static ViewPostProcessPipeline<S>,
+        S::ViewQuery,
+    );
+
+    fn run<'w>(
+        &self,
+        _graph: &mut RenderGraphContext,
+        render_context: &mut RenderContext<'w>,
+        (view_target, settings_index, view_pipeline, view): QueryItem<'w, '_, Self::ViewQuery>,
+        world: &'w World,
+    ) -> Result<(), NodeRunError> {
+        let post_process_pipeline = world.resource::<PostProcessPipeline<S>>();
+        let pipeline_cache = world.resource::<PipelineCache>();
+
+        let Some(pipeline) = pipeline_cache.get_render_pipeline(view_pipeline.id) else {
+            return Ok(());
+        };
+
+        let settings_uniforms = world.resource::<ComponentUniforms<S>>();
+        let Some(settings_binding) = settings_uniforms.uniforms().binding() else {
+            return Ok(());
+        };
+
+        let Some(extra_bindings) = S::extra_bindings(view, world) else {
+            return Ok(());
+        };
+
+        let post_process = view_target.post_process_write();
+        let entries: Vec<BindGroupEntry> = [
+            post_process.source.into_binding(),
+            post_process_pipeline.sampler.into_binding(),
+            settings_binding,
+        ]
+        .into_iter()
+        .chain(extra_bindings)
+        .enumerate()
+        .map(|(binding, resource)| BindGroupEntry {
+            binding: binding as u32,
+            resource,
+        })
+        .collect();
+        let bind_group = render_context.render_device().create_bind_group(
+            "post_process_bind_group",
+            &post_process_pipeline.layout,
+            &entries,
+        );
+
+        run_fullscreen_pass(
+            render_context,
+            "post_process_pass",
+            post_process.destination,
+            pipeline,
+            &bind_group,
+            settings_index.index(),
+        );
+
+        Ok(())
+    }
+}
+
+/// Draws a full screen triangle into `destination`, with the settings uniform at `settings_index`.
+pub fn run_fullscreen_pass(
+    render_context: &mut RenderContext,
+    label: &'static str,
+    destination: &TextureView,
+    pipeline: &RenderPipeline,
+    bind_group: &BindGroup,
+    settings_index: u32,
+) {
+    let mut render_pass = render_context.begin_tracked_render_pass(RenderPassDescriptor {
+        label: Some(label),
+        color_attachments: &[Some(RenderPassColorAttachment {
+            view: destination,
+            resolve_target: None,
+            ops: Operations::default(),
+            depth_slice: None,
+        })],
+        depth_stencil_attachment: None,
+        timestamp_writes: None,
+        occlusion_query_set: None,
+    });
+
+    render_pass.set_render_pipeline(pipeline);
+    render_pass.set_bind_group(0, bind_group, &[settings_index]);
+    render_pass.draw(0..3, 0..1);
+}
+
+/// A pipeline drawing a full screen triangle with the given fragment entry point.
+pub fn fullscreen_pipeline_descriptor(
+    fullscreen_shader: &FullscreenShader,
+    label: &'static str,
+    layout: &BindGroupLayout,
+    shader: Handle<Shader>,
+    entry_point: &'static str,
+    format: TextureFormat,
+) -> RenderPipelineDescriptor {
+    RenderPipelineDescriptor {
+        label: Some(label.into()),
+        layout: vec![layout.clone()],
+        vertex: fullscreen_shader.to_vertex_state(),
+        fragment: Some(FragmentState {
+            shader,
+            shader_defs: vec![],
+            entry_point: Some(entry_point.into()),
+            targets: vec![Some(ColorTargetState {
+                format,
+                blend: None,
+                write_mask: ColorWrites::ALL,
+            })],
+        }),
+        primitive: PrimitiveState::default(),
+        depth_stencil: None,
+        multisample: MultisampleState::default(),
+        push_constant_ranges: vec![],
+        zero_initialize_workgroup_memory: true,
+    }
+}
+
+#[derive(Resource)]
+struct PostProcessPipeline<S> {
+    layout: BindGroupLayout,
+    sampler: Sampler,
+    shader: Handle<Shader>,
+    fullscreen_shader: FullscreenShader,
+    marker: PhantomData<S>,
+}
+
+impl<S: PostProcessSettings> FromWorld for PostProcessPipeline<S> {
+    fn from_world(world: &mut World) -> Self {
+        let render_device = world.resource::<RenderDevice>();
+        let entries: Vec<BindGroupLayoutEntry> = [
+            texture_2d(TextureSampleType::Float { filterable: true }),
+            sampler(SamplerBindingType::Filtering),
+            uniform_buffer::<S>(true),
+        ]
+        .into_iter()
+        .chain(S::extra_layout_entries())
+        .enumerate()
+        .map(|(binding, entry)| entry.build(binding as u32, ShaderStages::FRAGMENT))
+        .collect();
+        let layout =
+            render_device.create_bind_group_layout("post_process_bind_group_layout", &entries);
+
+        let sampler = render_device.create_sampler(&SamplerDescriptor::default());
+
+        Self {
+            layout,
+            sampler,
+            shader: world.load_asset(S::SHADER),
+            fullscreen_shader: world.resource::<FullscreenShader>().clone(),
+            marker: PhantomData,
+        }
+    }
+}
+
+impl<S: PostProcessSettings> SpecializedRenderPipeline for PostProcessPipeline<S> {
+    type Key = TextureFormat;
+
+    fn specialize(&self, format: TextureFormat) -> RenderPipelineDescriptor {
+        fullscreen_pipeline_descriptor(
+            &self.fullscreen_shader,
+            "post_process_pipeline",
+            &self.layout,
+            self.shader.clone(),
+            "fragment",
+            format,
+        )
+    }
+}
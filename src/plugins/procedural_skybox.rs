@@ -1,48 +1,245 @@
+use std::sync::{
+    atomic::{AtomicU32, Ordering},
+    Arc,
+};
+
 use bevy::{
+    asset::RenderAssetUsages,
+    core_pipeline::{FullscreenShader, Skybox},
+    light::{EnvironmentMapLight, GeneratedEnvironmentMapLight},
+    pbr::generate::{GeneratorBindGroups, GeneratorPipelines},
     prelude::*,
-    render::render_resource::{AsBindGroup, FrontFace},
+    render::{
+        extract_resource::{ExtractResource, ExtractResourcePlugin},
+        render_asset::RenderAssets,
+        render_resource::{
+            binding_types::uniform_buffer, AsBindGroup, BindGroupEntries, BindGroupLayout,
+            BindGroupLayoutEntries, CachedRenderPipelineId, CommandEncoderDescriptor, Extent3d,
+            FrontFace, Operations, PipelineCache, RenderPassColorAttachment, RenderPassDescriptor,
+            ShaderStages, ShaderType, TextureDimension, TextureFormat, TextureUsages,
+            TextureViewDescriptor, TextureViewDimension, UniformBuffer,
+        },
+        renderer::{RenderDevice, RenderQueue},
+        texture::GpuImage,
+        Render, RenderApp, RenderSystems,
+    },
     shader::ShaderRef,
 };
+use rand::{rngs::StdRng, Rng, SeedableRng};
 
-use crate::core::main_camera::MainCamera;
+use crate::{
+    core::{distribution::random_direction, main_camera::MainCamera},
+    plugins::post_process::fullscreen_pipeline_descriptor,
+    resources::world_seed::WorldSeed,
+};
 
 pub struct ProceduralSkyboxPlugin;
 
 impl Plugin for ProceduralSkyboxPlugin {
     fn build(&self, app: &mut App) {
-        app.add_plugins(MaterialPlugin::<ProceduralSkyboxMaterial>::default())
-            .add_systems(Startup, setup)
-            .add_systems(Update, move_with_camera)
-            .add_systems(Update, update_material);
+        app.add_plugins((
+            MaterialPlugin::<ProceduralSkyboxMaterial>::default(),
+            ExtractResourcePlugin::<SkyboxBake>::default(),
+        ))
+        .add_systems(Startup, setup)
+        .add_systems(Update, move_with_camera)
+        .add_systems(
+            Update,
+            (
+                regenerate_skybox,
+                update_skybox_mode,
+                update_material,
+                request_skybox_bake,
+                update_environment_map,
+            )
+                .chain(),
+        );
+        let Some(render_app) = app.get_sub_app_mut(RenderApp) else {
+            return;
+        };
+        render_app.init_resource::<BakedSkybox>().add_systems(
+            Render,
+            (
+                bake_skybox.in_set(RenderSystems::PrepareResources),
+                report_filtered_bake.in_set(RenderSystems::Cleanup),
+            ),
+        );
     }
+
+    fn finish(&self, app: &mut App) {
+        let Some(render_app) = app.get_sub_app_mut(RenderApp) else {
+            return;
+        };
+
+        render_app.init_resource::<SkyboxBakePipeline>();
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Default)]
+pub enum SkyboxMode {
+    /// Evaluates the material every frame on a sphere around the camera.
+    Sphere,
+    /// Renders the material into a cubemap whenever its parameters change, and uses that as the
+    /// camera's skybox and environment map.
+    #[default]
+    Baked,
 }
 
 #[derive(Component)]
 pub struct ProceduralSkybox {
     pub material: Handle<ProceduralSkyboxMaterial>,
+    /// Changing the seed regenerates every skybox parameter.
+    pub seed: u64,
+    pub mode: SkyboxMode,
 }
 
+/// The cubemap the skybox is baked into, and the parameters it was last requested with.
+#[derive(Resource, Clone, ExtractResource)]
+struct SkyboxBake {
+    image: Handle<Image>,
+    parameters: Option<SkyboxParameters>,
+    generation: u32,
+    /// Latest generation filtered into the environment map, set by the render world.
+    filtered: Arc<AtomicU32>,
+}
+
+const BAKE_SIZE: u32 = 1024;
+const BAKE_FORMAT: TextureFormat = TextureFormat::Rgba16Float;
+// Matches the unlit sphere at the camera's default exposure.
+const SKYBOX_BRIGHTNESS: f32 = 1000.0;
+
 fn setup(
     mut commands: Commands,
     mut meshes: ResMut<Assets<Mesh>>,
     mut materials: ResMut<Assets<ProceduralSkyboxMaterial>>,
+    mut images: ResMut<Assets<Image>>,
+    world_seed: Res<WorldSeed>,
 ) {
+    let seed = world_seed.derive("skybox");
     let material = materials.add(ProceduralSkyboxMaterial {
         camera_position: Vec3::ZERO,
+        parameters: SkyboxParameters::from_seed(seed),
     });
 
     commands.spawn((
         ProceduralSkybox {
             material: material.clone(),
+            seed,
+            mode: SkyboxMode::default(),
         },
         Mesh3d(meshes.add(Mesh::from(Sphere { radius: 500.0 }))),
         MeshMaterial3d(material),
     ));
+
+    let mut cubemap = Image::new_fill(
+        Extent3d {
+            width: BAKE_SIZE,
+            height: BAKE_SIZE,
+            depth_or_array_layers: 6,
+        },
+        TextureDimension::D2,
+        &[0; 8],
+        BAKE_FORMAT,
+        RenderAssetUsages::RENDER_WORLD,
+    );
+    cubemap.texture_descriptor.usage |= TextureUsages::RENDER_ATTACHMENT;
+    cubemap.texture_view_descriptor = Some(TextureViewDescriptor {
+        dimension: Some(TextureViewDimension::Cube),
+        ..default()
+    });
+    commands.insert_resource(SkyboxBake {
+        image: images.add(cubemap),
+        parameters: None,
+        generation: 0,
+        filtered: default(),
+    });
+}
+
+// Shows either the sphere or the baked cubemap, never both.
+fn update_skybox_mode(
+    mut commands: Commands,
+    mut bake: ResMut<SkyboxBake>,
+    mut skybox_query: Query<(&ProceduralSkybox, &mut Visibility), Changed<ProceduralSkybox>>,
+    main_camera_query: Query<Entity, With<MainCamera>>,
+) {
+    for (skybox, mut visibility) in &mut skybox_query {
+        let Ok(main_camera_entity) = main_camera_query.single() else {
+            continue;
+        };
+        match skybox.mode {
+            SkyboxMode::Sphere => {
+                *visibility = Visibility::Inherited;
+                commands.entity(main_camera_entity).remove::<(
+                    Skybox,
+                    GeneratedEnvironmentMapLight,
+                    EnvironmentMapLight,
+                )>();
+            }
+            SkyboxMode::Baked => {
+                *visibility = Visibility::Hidden;
+                commands.entity(main_camera_entity).insert(Skybox {
+                    image: bake.image.clone(),
+                    brightness: SKYBOX_BRIGHTNESS,
+                    ..default()
+                });
+                // Bakes again, so the environment map removed with the sphere is filtered anew.
+                bake.parameters = None;
+            }
+        }
+    }
+}
+
+fn request_skybox_bake(
+    mut bake: ResMut<SkyboxBake>,
+    materials: Res<Assets<ProceduralSkyboxMaterial>>,
+    skybox_query: Query<&ProceduralSkybox>,
+) {
+    if let Ok(skybox) = skybox_query.single()
+        && skybox.mode == SkyboxMode::Baked
+        && let Some(material) = materials.get(&skybox.material)
+        && bake.parameters != Some(material.parameters)
+    {
+        bake.parameters = Some(material.parameters);
+        bake.generation += 1;
+    }
+}
+
+// Filtering the environment map from the 1024² cubemap is expensive, so it only runs until the
+// render world reports the latest bake filtered. The static `EnvironmentMapLight` left behind
+// keeps the result, and is filtered into again on the next bake.
+fn update_environment_map(
+    mut commands: Commands,
+    bake: Res<SkyboxBake>,
+    skybox_query: Query<&ProceduralSkybox>,
+    main_camera_query: Query<(Entity, Has<GeneratedEnvironmentMapLight>), With<MainCamera>>,
+) {
+    let (Ok(skybox), Ok((main_camera_entity, filtering))) =
+        (skybox_query.single(), main_camera_query.single())
+    else {
+        return;
+    };
+    if skybox.mode != SkyboxMode::Baked {
+        return;
+    }
+    let filtered = bake.filtered.load(Ordering::Acquire) == bake.generation;
+    if filtering && filtered {
+        commands
+            .entity(main_camera_entity)
+            .remove::<GeneratedEnvironmentMapLight>();
+    } else if !filtering && !filtered {
+        commands
+            .entity(main_camera_entity)
+            .insert(GeneratedEnvironmentMapLight {
+                environment_map: bake.image.clone(),
+                intensity: SKYBOX_BRIGHTNESS,
+                ..default()
+            });
+    }
 }
 
 fn move_with_camera(
-    mut skybox_query: Query<&mut Transform, (With<ProceduralSkybox>, Without<Camera>)>,
-    camera_query: Query<&Transform, (With<Camera>, Without<ProceduralSkybox>)>,
+    mut skybox_query: Query<&mut Transform, (With<ProceduralSkybox>, Without<MainCamera>)>,
+    camera_query: Query<&Transform, (With<MainCamera>, Without<ProceduralSkybox>)>,
 ) {
     if let Ok(mut skybox_transform) = skybox_query.single_mut()
         && let Ok(camera_transform) = camera_query.single()
@@ -51,12 +248,24 @@ fn move_with_camera(
     }
 }
 
+fn regenerate_skybox(
+    mut materials: ResMut<Assets<ProceduralSkyboxMaterial>>,
+    skybox_query: Query<&ProceduralSkybox, Changed<ProceduralSkybox>>,
+) {
+    for skybox in &skybox_query {
+        if let Some(material) = materials.get_mut(&skybox.material) {
+            material.parameters = SkyboxParameters::from_seed(skybox.seed);
+        }
+    }
+}
+
 fn update_material(
     mut materials: ResMut<Assets<ProceduralSkyboxMaterial>>,
     skybox_query: Query<&ProceduralSkybox>,
-    camera_query: Query<&Transform, With<Camera>>,
+    camera_query: Query<&Transform, With<MainCamera>>,
 ) {
     if let Ok(skybox) = skybox_query.single()
+        && skybox.mode == SkyboxMode::Sphere
         && let Some(material) = materials.get_mut(&skybox.material)
         && let Ok(camera_transform) = camera_query.single()
     {
@@ -64,10 +273,121 @@ fn update_material(
     }
 }
 
+pub const MAX_SKYBOX_BODIES: usize = 4;
+
+/// A distant planet or sun, drawn as a disc of `angular_radius` radians.
+#[derive(Clone, Copy, Default, PartialEq, ShaderType)]
+pub struct SkyboxBody {
+    pub direction: Vec3,
+    pub angular_radius: f32,
+    pub color: Vec3,
+    /// Zero for a planet lit from `light_direction`, otherwise the glow strength of a sun.
+    pub emission: f32,
+}
+
+#[derive(Clone, Copy, PartialEq, ShaderType)]
+pub struct SkyboxParameters {
+    /// Offsets every noise lookup, so each seed gets its own sky.
+    pub seed_offset: Vec3,
+    pub background_low: Vec3,
+    pub background_high: Vec3,
+    /// Star cells around the sky per unit of view direction.
+    pub star_scale: f32,
+    /// Fraction of cells that hold a star.
+    pub star_density: f32,
+    pub star_size: f32,
+    /// Peak star brightness. Values above one make the brightest stars bloom.
+    pub star_brightness: f32,
+    /// How much brightness varies between stars, from 0 (none) to 1.
+    pub star_brightness_variation: f32,
+    /// Range of star temperatures in Kelvin, mapped to blackbody colors.
+    pub star_temperature_range: Vec2,
+    pub galaxy_normal: Vec3,
+    pub galaxy_color: Vec3,
+    pub galaxy_width: f32,
+    pub galaxy_intensity: f32,
+    pub wisp_color_a: Vec3,
+    pub wisp_color_b: Vec3,
+    pub wisp_scale: f32,
+    pub wisp_intensity: f32,
+    /// Direction the light travels in, used to shade planets.
+    pub light_direction: Vec3,
+    pub body_count: u32,
+    pub bodies: [SkyboxBody; MAX_SKYBOX_BODIES],
+}
+
+impl SkyboxParameters {
+    pub fn from_seed(seed: u64) -> Self {
+        let mut rng = StdRng::seed_from_u64(seed);
+        let offset_range = -1000.0..1000.0;
+        let seed_offset = Vec3::new(
+            rng.gen_range(offset_range.clone()),
+            rng.gen_range(offset_range.clone()),
+            rng.gen_range(offset_range),
+        );
+        let mut hue_color = |hue: f32, saturation: f32, lightness: f32| {
+            let hue = (hue + rng.gen_range(-30.0..30.0)).rem_euclid(360.0);
+            Color::hsl(hue, saturation, lightness).to_linear().to_vec3()
+        };
+        let galaxy_color = hue_color(40.0, 0.35, 0.6);
+        let wisp_color_a = hue_color(200.0, 0.6, 0.4);
+        let wisp_color_b = hue_color(300.0, 0.6, 0.45);
+
+        let mut bodies = [SkyboxBody::default(); MAX_SKYBOX_BODIES];
+        // The first body is always a sun, which the scene's directional light comes from.
+        let body_count = rng.gen_range(1..=3);
+        for (index, body) in bodies.iter_mut().take(body_count).enumerate() {
+            let is_sun = index == 0;
+            *body = SkyboxBody {
+                direction: random_direction(&mut rng),
+                angular_radius: if is_sun {
+                    rng.gen_range(0.01..0.03)
+                } else {
+                    rng.gen_range(0.02..0.08)
+                },
+                color: if is_sun {
+                    Vec3::new(1.0, rng.gen_range(0.7..0.95), rng.gen_range(0.4..0.8))
+                } else {
+                    Color::hsl(rng.gen_range(0.0..360.0), rng.gen_range(0.2..0.6), 0.4)
+                        .to_linear()
+                        .to_vec3()
+                },
+                emission: if is_sun { rng.gen_range(4.0..8.0) } else { 0.0 },
+            };
+        }
+        let light_direction = -bodies[0].direction;
+
+        Self {
+            seed_offset,
+            background_low: Vec3::new(0.04, 0.05, 0.05),
+            background_high: Vec3::new(0.04, 0.03, 0.07),
+            star_scale: 50.0,
+            star_density: rng.gen_range(0.4..0.8),
+            star_size: 0.003,
+            star_brightness: 4.0,
+            star_brightness_variation: 0.8,
+            star_temperature_range: Vec2::new(2500.0, 12000.0),
+            galaxy_normal: random_direction(&mut rng),
+            galaxy_color,
+            galaxy_width: rng.gen_range(0.1..0.3),
+            galaxy_intensity: rng.gen_range(0.05..0.15),
+            wisp_color_a,
+            wisp_color_b,
+            wisp_scale: rng.gen_range(2.0..4.0),
+            wisp_intensity: rng.gen_range(0.02..0.08),
+            light_direction,
+            body_count: body_count as u32,
+            bodies,
+        }
+    }
+}
+
 #[derive(AsBindGroup, TypePath, Asset, Clone)]
 pub struct ProceduralSkyboxMaterial {
     #[uniform(0)]
     camera_position: Vec3,
+    #[uniform(1)]
+    pub parameters: SkyboxParameters,
 }
 
 impl Material for ProceduralSkyboxMaterial {
@@ -85,3 +405,144 @@ impl Material for ProceduralSkyboxMaterial {
         Ok(())
     }
 }
+
+#[derive(ShaderType)]
+struct SkyboxBakeUniform {
+    parameters: SkyboxParameters,
+    face: u32,
+}
+
+#[derive(Resource, Default)]
+struct BakedSkybox {
+    generation: u32,
+}
+
+// Renders all six cubemap faces in a single submission outside the render graph, so the result
+// is ready before the environment map is filtered from it later in the frame.
+fn bake_skybox(
+    bake: Option<Res<SkyboxBake>>,
+    mut baked: ResMut<BakedSkybox>,
+    pipeline: Res<SkyboxBakePipeline>,
+    pipeline_cache: Res<PipelineCache>,
+    images: Res<RenderAssets<GpuImage>>,
+    render_device: Res<RenderDevice>,
+    render_queue: Res<RenderQueue>,
+) {
+    let Some(bake) = bake else {
+        return;
+    };
+    let Some(parameters) = bake.parameters else {
+        return;
+    };
+    if baked.generation == bake.generation {
+        return;
+    }
+    let (Some(render_pipeline), Some(cubemap)) = (
+        pipeline_cache.get_render_pipeline(pipeline.pipeline_id),
+        images.get(&bake.image),
+    ) else {
+        return;
+    };
+
+    let mut encoder = render_device.create_command_encoder(&CommandEncoderDescriptor {
+        label: Some("skybox_bake_encoder"),
+    });
+    for face in 0..6 {
+        let mut uniform = UniformBuffer::from(SkyboxBakeUniform { parameters, face });
+        uniform.write_buffer(&render_device, &render_queue);
+        let Some(uniform_binding) = uniform.binding() else {
+            return;
+        };
+        let bind_group = render_device.create_bind_group(
+            "skybox_bake_bind_group",
+            &pipeline.layout,
+            &BindGroupEntries::single(uniform_binding),
+        );
+        let face_view = cubemap.texture.create_view(&TextureViewDescriptor {
+            label: Some("skybox_bake_face"),
+            dimension: Some(TextureViewDimension::D2),
+            base_array_layer: face,
+            array_layer_count: Some(1),
+            ..default()
+        });
+        let mut render_pass = encoder.begin_render_pass(&RenderPassDescriptor {
+            label: Some("skybox_bake_pass"),
+            color_attachments: &[Some(RenderPassColorAttachment {
+                view: &face_view,
+                resolve_target: None,
+                ops: Operations::default(),
+                depth_slice: None,
+            })],
+            depth_stencil_attachment: None,
+            timestamp_writes: None,
+            occlusion_query_set: None,
+        });
+        render_pass.set_pipeline(render_pipeline);
+        render_pass.set_bind_group(0, &*bind_group, &[]);
+        render_pass.draw(0..3, 0..1);
+    }
+    render_queue.submit([encoder.finish()]);
+    baked.generation = bake.generation;
+}
+
+// Runs after the render graph. Once a bake has gone through the environment map generator, with
+// its pipelines compiled, the main world can stop filtering it.
+fn report_filtered_bake(
+    bake: Option<Res<SkyboxBake>>,
+    baked: Res<BakedSkybox>,
+    generator_pipelines: Option<Res<GeneratorPipelines>>,
+    pipeline_cache: Res<PipelineCache>,
+    generator_query: Query<(), With<GeneratorBindGroups>>,
+) {
+    let (Some(bake), Some(generator_pipelines)) = (bake, generator_pipelines) else {
+        return;
+    };
+    let pipelines_ready = [
+        generator_pipelines.downsample_first,
+        generator_pipelines.downsample_second,
+        generator_pipelines.copy,
+        generator_pipelines.radiance,
+        generator_pipelines.irradiance,
+    ]
+    .into_iter()
+    .all(|id| pipeline_cache.get_compute_pipeline(id).is_some());
+    if pipelines_ready && baked.generation == bake.generation && !generator_query.is_empty() {
+        bake.filtered.store(baked.generation, Ordering::Release);
+    }
+}
+
+#[derive(Resource)]
+struct SkyboxBakePipeline {
+    layout: BindGroupLayout,
+    pipeline_id: CachedRenderPipelineId,
+}
+
+impl FromWorld for SkyboxBakePipeline {
+    fn from_world(world: &mut World) -> Self {
+        let render_device = world.resource::<RenderDevice>();
+        let layout = render_device.create_bind_group_layout(
+            "skybox_bake_bind_group_layout",
+            &BindGroupLayoutEntries::single(
+                ShaderStages::FRAGMENT,
+                uniform_buffer::<SkyboxBakeUniform>(false),
+            ),
+        );
+        let shader = world.load_asset("shaders/procedural_skybox_bake.wgsl");
+        let descriptor = fullscreen_pipeline_descriptor(
+            world.resource::<FullscreenShader>(),
+            "skybox_bake_pipeline",
+            &layout,
+            shader,
+            "fragment",
+            BAKE_FORMAT,
+        );
+        let pipeline_id = world
+            .resource::<PipelineCache>()
+            .queue_render_pipeline(descriptor);
+
+        Self {
+            layout,
+            pipeline_id,
+        }
+    }
+}
@@ -0,0 +1,72 @@
+use std::{any::TypeId, marker::PhantomData};
+
+use bevy::{
+    asset::{io::Reader, ron, AssetLoader, LoadContext},
+    prelude::*,
+    reflect::{serde::TypedReflectDeserializer, GetTypeRegistration, TypeRegistryArc},
+};
+
+/// Adds the asset `A`, loaded from RON files ending in one of `extensions`.
+pub struct RonAssetPlugin<A> {
+    extensions: &'static [&'static str],
+    marker: PhantomData<fn() -> A>,
+}
+
+impl<A> RonAssetPlugin<A> {
+    pub fn new(extensions: &'static [&'static str]) -> Self {
+        Self {
+            extensions,
+            marker: PhantomData,
+        }
+    }
+}
+
+impl<A: Asset + FromReflect + GetTypeRegistration> Plugin for RonAssetPlugin<A> {
+    fn build(&self, app: &mut App) {
+        let loader = RonAssetLoader::<A> {
+            registry: app.world().resource::<AppTypeRegistry>().0.clone(),
+            extensions: self.extensions,
+            marker: PhantomData,
+        };
+        app.init_asset::<A>()
+            .register_type::<A>()
+            .register_asset_loader(loader);
+    }
+}
+
+/// Loads `A`s from RON, in the same format as reflected scenes.
+struct RonAssetLoader<A> {
+    registry: TypeRegistryArc,
+    extensions: &'static [&'static str],
+    marker: PhantomData<fn() -> A>,
+}
+
+impl<A: Asset + FromReflect> AssetLoader for RonAssetLoader<A> {
+    type Asset = A;
+    type Settings = ();
+    type Error = BevyError;
+
+    async fn load(
+        &self,
+        reader: &mut dyn Reader,
+        _settings: &(),
+        _load_context: &mut LoadContext<'_>,
+    ) -> Result<A, BevyError> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes).await?;
+        let registry = self.registry.read();
+        let registration = registry
+            .get(TypeId::of::<A>())
+            .ok_or_else(|| format!("{} is not registered", A::type_path()))?;
+        let reflected = ron::Options::default().from_bytes_seed(
+            &bytes,
+            TypedReflectDeserializer::new(registration, &registry),
+        )?;
+        Ok(A::from_reflect(reflected.as_partial_reflect())
+            .ok_or_else(|| format!("invalid {}", A::short_type_path()))?)
+    }
+
+    fn extensions(&self) -> &[&str] {
+        self.extensions
+    }
+}
@@ -1,16 +1,165 @@
-use bevy::prelude::*;
+use bevy::{
+    light::{CascadeShadowConfigBuilder, DirectionalLightShadowMap},
+    prelude::*,
+};
+
+use crate::{
+    core::{damage::Destroyed, lighting::LightFlash, weapon::WeaponFired},
+    plugins::procedural_skybox::{ProceduralSkybox, ProceduralSkyboxMaterial},
+};
+
 pub struct SceneLightingPlugin;
 
+const SUN_ILLUMINANCE: f32 = 10_000.0;
+const LIGHT_POOL_SIZE: usize = 16;
+const MAX_FLASHES_PER_FRAME: usize = 4;
+
+/// The directional light, shining from the skybox's sun.
+#[derive(Component)]
+pub struct SunLight;
+
+#[derive(Component, Default)]
+struct PooledLight {
+    intensity: f32,
+    duration: f32,
+    remaining: f32,
+}
+
 fn setup_lights(mut commands: Commands, mut ambient_light: ResMut<AmbientLight>) {
-    commands.spawn(DirectionalLight {
-        illuminance: 10_000.0,
-        ..default()
-    });
+    commands.spawn((
+        SunLight,
+        DirectionalLight {
+            illuminance: SUN_ILLUMINANCE,
+            shadows_enabled: true,
+            ..default()
+        },
+        // Most of the shadow resolution goes to the rocks around the ship, while the last cascade
+        // still reaches across the asteroid field.
+        CascadeShadowConfigBuilder {
+            num_cascades: 4,
+            minimum_distance: 0.1,
+            first_cascade_far_bound: 25.0,
+            maximum_distance: 400.0,
+            overlap_proportion: 0.2,
+        }
+        .build(),
+    ));
+    for _ in 0..LIGHT_POOL_SIZE {
+        commands.spawn((
+            PooledLight::default(),
+            PointLight {
+                intensity: 0.0,
+                ..default()
+            },
+            Visibility::Hidden,
+        ));
+    }
     ambient_light.brightness = 200.0;
 }
 
+pub fn align_sun_light(
+    materials: Res<Assets<ProceduralSkyboxMaterial>>,
+    skybox_query: Query<&ProceduralSkybox>,
+    mut sun_query: Query<(&mut Transform, &mut DirectionalLight), With<SunLight>>,
+) {
+    if let Ok(skybox) = skybox_query.single()
+        && let Some(material) = materials.get(&skybox.material)
+        && let Ok((mut transform, mut light)) = sun_query.single_mut()
+    {
+        let sun = material.parameters.bodies[0];
+        transform.set_if_neq(
+            Transform::default().looking_to(material.parameters.light_direction, Vec3::Y),
+        );
+        let color = Color::linear_rgb(sun.color.x, sun.color.y, sun.color.z);
+        if light.color != color {
+            light.color = color;
+        }
+    }
+}
+
+fn request_light_flashes(
+    mut fired: MessageReader<WeaponFired>,
+    mut destroyed: MessageReader<Destroyed>,
+    mut flashes: MessageWriter<LightFlash>,
+) {
+    for WeaponFired { hardpoint, .. } in fired.read() {
+        flashes.write(LightFlash {
+            position: hardpoint.translation,
+            color: Color::srgb(0.5, 1.0, 0.5),
+            intensity: 200_000.0,
+            range: 15.0,
+            duration: 0.08,
+        });
+    }
+    for Destroyed { transform, .. } in destroyed.read() {
+        flashes.write(LightFlash {
+            position: transform.translation,
+            color: Color::srgb(1.0, 0.6, 0.25),
+            intensity: 4_000_000.0,
+            range: 60.0,
+            duration: 0.6,
+        });
+    }
+}
+
+fn update_light_pool(
+    time: Res<Time>,
+    mut flashes: MessageReader<LightFlash>,
+    mut light_query: Query<(
+        &mut PooledLight,
+        &mut PointLight,
+        &mut Transform,
+        &mut Visibility,
+    )>,
+) {
+    for (mut pooled, mut point_light, _, mut visibility) in &mut light_query {
+        if pooled.remaining <= 0.0 {
+            continue;
+        }
+        pooled.remaining -= time.delta_secs();
+        if pooled.remaining > 0.0 {
+            point_light.intensity = pooled.intensity * (pooled.remaining / pooled.duration).powi(2);
+        } else {
+            point_light.intensity = 0.0;
+            *visibility = Visibility::Hidden;
+        }
+    }
+
+    // When more flashes arrive than the cap allows, the brightest ones are kept.
+    let mut requested: Vec<&LightFlash> = flashes.read().collect();
+    requested.sort_by(|a, b| b.intensity.total_cmp(&a.intensity));
+    for flash in requested.into_iter().take(MAX_FLASHES_PER_FRAME) {
+        // Takes a free light, or else the one closest to fading out.
+        let Some((mut pooled, mut point_light, mut transform, mut visibility)) = light_query
+            .iter_mut()
+            .min_by(|a, b| a.0.remaining.total_cmp(&b.0.remaining))
+        else {
+            break;
+        };
+        *pooled = PooledLight {
+            intensity: flash.intensity,
+            duration: flash.duration,
+            remaining: flash.duration,
+        };
+        point_light.color = flash.color;
+        point_light.intensity = flash.intensity;
+        point_light.range = flash.range;
+        transform.translation = flash.position;
+        *visibility = Visibility::Visible;
+    }
+}
+
 impl Plugin for SceneLightingPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(Startup, setup_lights);
+        app.add_message::<LightFlash>()
+            .insert_resource(DirectionalLightShadowMap { size: 4096 })
+            .add_systems(Startup, setup_lights)
+            .add_systems(
+                Update,
+                (
+                    align_sun_light,
+                    (request_light_flashes, update_light_pool).chain(),
+                ),
+            );
     }
 }
@@ -0,0 +1,190 @@
+use bevy::prelude::*;
+
+use crate::{
+    core::{
+        main_camera::MainCamera,
+        nebula::NebulaExposure,
+        player::Player,
+        target::{LockedTarget, Targetable},
+    },
+    plugins::outline::Outlined,
+};
+
+pub struct TargetingPlugin;
+
+impl Plugin for TargetingPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Startup, add_components_player).add_systems(
+            Update,
+            (
+                handle_targeting_input,
+                validate_locked_target,
+                outline_locked_target,
+            )
+                .chain(),
+        );
+    }
+}
+
+#[derive(Component, Debug)]
+pub struct LockOn {
+    pub range: f32,
+    pub max_angle: f32,
+    pub break_range: f32,
+    outlined: Option<Entity>,
+}
+
+impl LockOn {
+    pub fn new() -> Self {
+        Self {
+            range: 400.0,
+            max_angle: 0.35,
+            break_range: 500.0,
+            outlined: None,
+        }
+    }
+}
+
+enum TargetSelection {
+    NearestToCrosshair,
+    Next,
+    Previous,
+}
+
+fn add_components_player(mut commands: Commands, player_query: Query<Entity, With<Player>>) {
+    if let Ok(player_entity) = player_query.single() {
+        commands.entity(player_entity).insert(LockOn::new());
+    }
+}
+
+fn handle_targeting_input(
+    mut commands: Commands,
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    player_query: Query<(Entity, &Transform, &LockOn, Option<&LockedTarget>), With<Player>>,
+    exposure_query: Query<&NebulaExposure, With<Player>>,
+    camera_query: Query<&GlobalTransform, With<MainCamera>>,
+    targetable_query: Query<(Entity, &GlobalTransform), With<Targetable>>,
+) {
+    let selection = if keyboard_input.just_pressed(KeyCode::KeyT) {
+        TargetSelection::NearestToCrosshair
+    } else if keyboard_input.just_pressed(KeyCode::Tab) {
+        if keyboard_input.pressed(KeyCode::ShiftLeft) {
+            TargetSelection::Previous
+        } else {
+            TargetSelection::Next
+        }
+    } else {
+        return;
+    };
+    let Ok((player_entity, player_transform, lock_on, locked_target)) = player_query.single()
+    else {
+        return;
+    };
+    let Ok(camera_transform) = camera_query.single() else {
+        return;
+    };
+    let player_position = player_transform.translation;
+    let current = locked_target.map(|locked_target| locked_target.0);
+    let range = lock_on.range * sensor_factor(&exposure_query);
+
+    let target = match selection {
+        TargetSelection::NearestToCrosshair => {
+            let crosshair = camera_transform.forward().as_vec3();
+            targetable_query
+                .iter()
+                .filter(|(_, transform)| transform.translation().distance(player_position) <= range)
+                .map(|(entity, transform)| {
+                    let direction = transform.translation() - camera_transform.translation();
+                    (entity, direction.angle_between(crosshair))
+                })
+                .filter(|(_, angle)| *angle <= lock_on.max_angle)
+                .min_by(|a, b| a.1.total_cmp(&b.1))
+                .map(|(entity, _)| entity)
+        }
+        TargetSelection::Next | TargetSelection::Previous => {
+            let mut candidates: Vec<(Entity, f32)> = targetable_query
+                .iter()
+                .map(|(entity, transform)| {
+                    (entity, transform.translation().distance(player_position))
+                })
+                .filter(|(_, distance)| *distance <= range)
+                .collect();
+            candidates.sort_by(|a, b| a.1.total_cmp(&b.1));
+            if candidates.is_empty() {
+                None
+            } else {
+                let len = candidates.len();
+                let index = match (
+                    current.and_then(|current| {
+                        candidates.iter().position(|(entity, _)| *entity == current)
+                    }),
+                    selection,
+                ) {
+                    (Some(index), TargetSelection::Previous) => (index + len - 1) % len,
+                    (Some(index), _) => (index + 1) % len,
+                    (None, _) => 0,
+                };
+                Some(candidates[index].0)
+            }
+        }
+    };
+
+    match target {
+        Some(target) => {
+            commands.entity(player_entity).insert(LockedTarget(target));
+        }
+        None => {
+            commands.entity(player_entity).remove::<LockedTarget>();
+        }
+    }
+}
+
+fn validate_locked_target(
+    mut commands: Commands,
+    player_query: Query<(Entity, &Transform, &LockOn, &LockedTarget), With<Player>>,
+    exposure_query: Query<&NebulaExposure, With<Player>>,
+    target_query: Query<&GlobalTransform, With<Targetable>>,
+) {
+    if let Ok((player_entity, player_transform, lock_on, locked_target)) = player_query.single() {
+        // The lock survives occlusion and only breaks when the target is gone or out of range.
+        let in_range = target_query
+            .get(locked_target.0)
+            .is_ok_and(|target_transform| {
+                target_transform
+                    .translation()
+                    .distance(player_transform.translation)
+                    <= lock_on.break_range * sensor_factor(&exposure_query)
+            });
+        if !in_range {
+            commands.entity(player_entity).remove::<LockedTarget>();
+        }
+    }
+}
+
+// Dense nebula cuts sensor range, shrinking how far targets can be locked and held.
+fn sensor_factor(exposure_query: &Query<&NebulaExposure, With<Player>>) -> f32 {
+    exposure_query
+        .single()
+        .map_or(1.0, |exposure| exposure.sensor_factor)
+}
+
+fn outline_locked_target(
+    mut commands: Commands,
+    mut player_query: Query<(&mut LockOn, Option<&LockedTarget>), With<Player>>,
+) {
+    if let Ok((mut lock_on, locked_target)) = player_query.single_mut() {
+        let target = locked_target.map(|locked_target| locked_target.0);
+        if lock_on.outlined == target {
+            return;
+        }
+        if let Some(previous) = lock_on.outlined
+            && let Ok(mut entity_commands) = commands.get_entity(previous)
+        {
+            entity_commands.remove::<Outlined>();
+        }
+        if let Some(target) = target {
+            commands.entity(target).try_insert(Outlined);
+        }
+        lock_on.outlined = target;
+    }
+}
@@ -1,10 +1,7 @@
 use bevy::{
-    core_pipeline::{
-        core_3d::graph::{Core3d, Node3d},
-        prepass::ViewPrepassTextures,
-        FullscreenShader,
-    },
+    core_pipeline::{core_3d::graph::Core3d, prepass::ViewPrepassTextures, FullscreenShader},
     ecs::query::QueryItem,
+    platform::collections::HashMap,
     prelude::*,
     render::{
         extract_component::{
@@ -15,20 +12,37 @@ use bevy::{
             NodeRunError, RenderGraphContext, RenderGraphExt, RenderLabel, ViewNode, ViewNodeRunner,
         },
         render_resource::{
-            binding_types::{sampler, texture_2d, uniform_buffer},
+            binding_types::{
+                sampler, storage_buffer_read_only, texture_2d, texture_depth_2d_multisampled,
+                uniform_buffer,
+            },
             BindGroupEntries, BindGroupLayout, BindGroupLayoutEntries, CachedRenderPipelineId,
-            ColorTargetState, ColorWrites, FragmentState, MultisampleState, Operations,
-            PipelineCache, PrimitiveState, RenderPassColorAttachment, RenderPassDescriptor,
-            RenderPipelineDescriptor, Sampler, SamplerBindingType, SamplerDescriptor, ShaderStages,
-            ShaderType, TextureFormat, TextureSampleType,
+            Extent3d, FilterMode, PipelineCache, RenderPipelineDescriptor, Sampler,
+            SamplerBindingType, SamplerDescriptor, ShaderStages, ShaderType,
+            SpecializedRenderPipeline, SpecializedRenderPipelines, StorageBuffer,
+            TextureDescriptor, TextureDimension, TextureFormat, TextureSampleType, TextureUsages,
+            TextureView,
         },
-        renderer::{RenderContext, RenderDevice},
-        view::ViewTarget,
-        RenderApp,
+        renderer::{RenderContext, RenderDevice, RenderQueue},
+        view::{ExtractedView, ViewTarget},
+        Extract, ExtractSchedule, Render, RenderApp, RenderSystems,
     },
 };
 
-use crate::core::main_camera::MainCamera;
+use crate::{
+    core::{
+        main_camera::MainCamera,
+        nebula::{NebulaGradient, NebulaShape, NebulaVolume},
+    },
+    noise::nebula::nebula_noise,
+    plugins::{
+        post_process::{
+            fullscreen_pipeline_descriptor, register_post_process_effect, run_fullscreen_pass,
+            PostProcessStage,
+        },
+        scene_lighting::{align_sun_light, SunLight},
+    },
+};
 
 pub struct VolumetricNebulaPlugin;
 
@@ -46,37 +60,102 @@ fn add_components_main_camera(
                 camera_up: Vec3::ZERO,
                 camera_forward: Vec3::ZERO,
                 light_direction: Vec3::ZERO,
-                speed: 2.0,
-                scale: 0.1,
                 iso_value: 0.86,
-                step_count: 30,
-                step_distance: 3.0,
+                step_count: 64,
+                step_distance: 1.5,
+                near: 0.1,
+                tan_half_fov: 1.0,
+                aspect_ratio: 1.0,
+                light_color: Vec3::ONE,
+                light_intensity: 8.0,
+                ambient_color: Vec3::new(0.02, 0.02, 0.03),
+                density: 1.0,
+                absorption: 0.02,
+                scattering: 0.08,
+                anisotropy: 0.3,
+                shadow_step_count: 4,
+                shadow_step_distance: 4.0,
+                max_distance: 2000.0,
+                resolution_scale: 2.0,
+                history_blend: 0.9,
+                frame_index: 0,
+                previous_camera_position: Vec3::ZERO,
+                previous_camera_right: Vec3::ZERO,
+                previous_camera_up: Vec3::ZERO,
+                previous_camera_forward: Vec3::ZERO,
             });
     }
 }
 
-fn startup_settings(
-    light_query: Query<&Transform, With<DirectionalLight>>,
-    mut nebula_query: Query<&mut VolumetricNebulaSettings, With<Camera>>,
+fn spawn_nebula_volumes(mut commands: Commands) {
+    commands.spawn((
+        NebulaVolume {
+            shape: NebulaShape::Sphere { radius: 220.0 },
+            ..default()
+        },
+        Transform::from_xyz(0.0, 0.0, -120.0),
+    ));
+    commands.spawn((
+        NebulaVolume {
+            shape: NebulaShape::Box {
+                half_extents: Vec3::new(160.0, 50.0, 90.0),
+            },
+            density: 0.8,
+            gradient: NebulaGradient {
+                colors: [
+                    Vec4::new(0.9, 0.3, 0.1, 1.0),
+                    Vec4::new(1.0, 0.5, 0.2, 1.0),
+                    Vec4::new(0.9, 0.8, 0.4, 1.0),
+                    Vec4::new(1.0, 0.95, 0.8, 1.0),
+                ],
+                stops: Vec4::new(0.0, 0.25, 0.6, 1.0),
+            },
+            noise_scale: 0.06,
+            animation_speed: 0.2,
+            ..default()
+        },
+        Transform::from_xyz(380.0, 60.0, 150.0).with_rotation(Quat::from_rotation_y(0.6)),
+    ));
+}
+
+// Lights the nebula like the rest of the scene, once `align_sun_light` has turned the sun.
+fn follow_sun_light(
+    light_query: Query<(Ref<Transform>, Ref<DirectionalLight>), With<SunLight>>,
+    mut nebula_query: Query<&mut VolumetricNebulaSettings, With<MainCamera>>,
 ) {
-    if let Ok(light) = light_query.single() {
-        if let Ok(mut nebula) = nebula_query.single_mut() {
-            nebula.light_direction = light.forward().as_vec3();
-        }
+    if let Ok((light_transform, light)) = light_query.single()
+        && (light_transform.is_changed() || light.is_changed())
+        && let Ok(mut nebula) = nebula_query.single_mut()
+    {
+        nebula.light_direction = light_transform.forward().as_vec3();
+        nebula.light_color = light.color.to_linear().to_vec3();
     }
 }
 
 fn update_settings(
-    mut camera_query: Query<(&Transform, &mut VolumetricNebulaSettings), With<MainCamera>>,
+    mut camera_query: Query<
+        (&Transform, &Projection, &mut VolumetricNebulaSettings),
+        With<MainCamera>,
+    >,
     time: Res<Time>,
 ) {
     if let Ok(camera) = camera_query.single_mut() {
-        let mut settings = camera.1;
+        let mut settings = camera.2;
+        settings.previous_camera_position = settings.camera_position;
+        settings.previous_camera_right = settings.camera_right;
+        settings.previous_camera_up = settings.camera_up;
+        settings.previous_camera_forward = settings.camera_forward;
+        settings.frame_index = settings.frame_index.wrapping_add(1);
         settings.camera_position = camera.0.translation;
         settings.camera_right = camera.0.right().as_vec3();
         settings.camera_up = camera.0.up().as_vec3();
         settings.camera_forward = camera.0.forward().as_vec3();
         settings.time = time.elapsed_secs_wrapped();
+        if let Projection::Perspective(perspective) = camera.1 {
+            settings.near = perspective.near;
+            settings.tan_half_fov = (perspective.fov * 0.5).tan();
+            settings.aspect_ratio = perspective.aspect_ratio;
+        }
     }
 }
 
@@ -86,25 +165,32 @@ impl Plugin for VolumetricNebulaPlugin {
             ExtractComponentPlugin::<VolumetricNebulaSettings>::default(),
             UniformComponentPlugin::<VolumetricNebulaSettings>::default(),
         ))
-        .add_systems(Startup, add_components_main_camera)
-        .add_systems(PostStartup, startup_settings)
-        .add_systems(Update, update_settings);
+        .add_systems(Startup, (add_components_main_camera, spawn_nebula_volumes))
+        .add_systems(
+            Update,
+            (update_settings, follow_sun_light.after(align_sun_light)),
+        );
         let Some(render_app) = app.get_sub_app_mut(RenderApp) else {
             return;
         };
         render_app
+            .init_resource::<VolumetricNebulaTextures>()
+            .init_resource::<NebulaVolumeBuffer>()
+            .init_resource::<SpecializedRenderPipelines<VolumetricNebulaPipeline>>()
+            .add_systems(ExtractSchedule, extract_nebula_volumes)
+            .add_systems(
+                Render,
+                (
+                    prepare_composite_pipelines.in_set(RenderSystems::Prepare),
+                    (prepare_textures, prepare_nebula_volumes)
+                        .in_set(RenderSystems::PrepareResources),
+                ),
+            )
             .add_render_graph_node::<ViewNodeRunner<VolumetricNebulaNode>>(
                 Core3d,
                 VolumetricNebulaLabel,
-            )
-            .add_render_graph_edges(
-                Core3d,
-                (
-                    Node3d::Tonemapping,
-                    VolumetricNebulaLabel,
-                    Node3d::EndMainPassPostProcessing,
-                ),
             );
+        register_post_process_effect(app, PostProcessStage::Scene, 0, VolumetricNebulaLabel);
     }
 
     fn finish(&self, app: &mut App) {
@@ -124,15 +210,222 @@ pub struct VolumetricNebulaSettings {
     pub camera_up: Vec3,
     pub camera_forward: Vec3,
     pub light_direction: Vec3,
-    pub speed: f32,
-    pub scale: f32,
     pub iso_value: f32,
     pub step_count: i32,
     pub step_distance: f32,
+    pub near: f32,
+    pub tan_half_fov: f32,
+    pub aspect_ratio: f32,
+    pub light_color: Vec3,
+    pub light_intensity: f32,
+    pub ambient_color: Vec3,
+    pub density: f32,
+    pub absorption: f32,
+    pub scattering: f32,
+    pub anisotropy: f32,
+    pub shadow_step_count: i32,
+    pub shadow_step_distance: f32,
+    pub max_distance: f32,
+    pub resolution_scale: f32,
+    pub history_blend: f32,
+    pub frame_index: u32,
+    pub previous_camera_position: Vec3,
+    pub previous_camera_right: Vec3,
+    pub previous_camera_up: Vec3,
+    pub previous_camera_forward: Vec3,
+}
+
+impl VolumetricNebulaSettings {
+    // Mirrors `density_at` in `volumetric_nebula.wgsl`.
+    pub fn density_at<'a>(
+        &self,
+        world_position: Vec3,
+        volumes: impl IntoIterator<Item = (&'a GlobalTransform, &'a NebulaVolume)>,
+    ) -> f32 {
+        volumes
+            .into_iter()
+            .map(|(transform, volume)| {
+                GpuNebulaVolume::new(transform, volume).density_at(world_position, self)
+            })
+            .sum::<f32>()
+            * self.density
+    }
+}
+
+#[derive(Clone, Copy, ShaderType)]
+struct GpuNebulaVolume {
+    local_from_world: Mat4,
+    extents: Vec3,
+    shape: u32,
+    density: f32,
+    noise_scale: f32,
+    animation_speed: f32,
+    edge_falloff: f32,
+    gradient: NebulaGradient,
+}
+
+impl GpuNebulaVolume {
+    const SPHERE: u32 = 0;
+    const BOX: u32 = 1;
+
+    fn new(transform: &GlobalTransform, volume: &NebulaVolume) -> Self {
+        let (shape, extents) = match volume.shape {
+            NebulaShape::Sphere { radius } => (Self::SPHERE, Vec3::splat(radius)),
+            NebulaShape::Box { half_extents } => (Self::BOX, half_extents),
+        };
+        Self {
+            local_from_world: transform.affine().inverse().into(),
+            extents,
+            shape,
+            density: volume.density,
+            noise_scale: volume.noise_scale,
+            animation_speed: volume.animation_speed,
+            edge_falloff: volume.edge_falloff.clamp(1e-3, 1.0),
+            gradient: volume.gradient,
+        }
+    }
+
+    // Mirrors `volume_density` in `volumetric_nebula.wgsl`.
+    fn density_at(&self, world_position: Vec3, settings: &VolumetricNebulaSettings) -> f32 {
+        let local = self.local_from_world.transform_point3(world_position);
+        let edge = if self.shape == Self::SPHERE {
+            local.length() / self.extents.x
+        } else {
+            (local.abs() / self.extents).max_element()
+        };
+        if edge >= 1.0 {
+            return 0.0;
+        }
+        let mask = 1.0 - smoothstep(1.0 - self.edge_falloff, 1.0, edge);
+        let point =
+            world_position * self.noise_scale + Vec3::splat(settings.time * self.animation_speed);
+        (nebula_noise(point).length() - settings.iso_value).max(0.0) * self.density * mask
+    }
+}
+
+fn smoothstep(edge0: f32, edge1: f32, x: f32) -> f32 {
+    let t = ((x - edge0) / (edge1 - edge0)).clamp(0.0, 1.0);
+    t * t * (3.0 - 2.0 * t)
+}
+
+#[derive(Default, ShaderType)]
+struct GpuNebulaVolumes {
+    count: u32,
+    #[size(runtime)]
+    volumes: Vec<GpuNebulaVolume>,
+}
+
+#[derive(Resource, Default)]
+struct NebulaVolumeBuffer(StorageBuffer<GpuNebulaVolumes>);
+
+fn extract_nebula_volumes(
+    mut buffer: ResMut<NebulaVolumeBuffer>,
+    volume_query: Extract<Query<(&GlobalTransform, &NebulaVolume)>>,
+) {
+    let volumes = buffer.0.get_mut();
+    volumes.volumes.clear();
+    volumes.volumes.extend(
+        volume_query
+            .iter()
+            .map(|(transform, volume)| GpuNebulaVolume::new(transform, volume)),
+    );
+    volumes.count = volumes.volumes.len() as u32;
+}
+
+fn prepare_nebula_volumes(
+    render_device: Res<RenderDevice>,
+    render_queue: Res<RenderQueue>,
+    mut buffer: ResMut<NebulaVolumeBuffer>,
+) {
+    buffer.0.write_buffer(&render_device, &render_queue);
 }
 
 #[derive(Debug, Hash, PartialEq, Eq, Clone, RenderLabel)]
-struct VolumetricNebulaLabel;
+pub struct VolumetricNebulaLabel;
+
+const NEBULA_TEXTURE_FORMAT: TextureFormat = TextureFormat::Rgba16Float;
+
+// Low resolution march output plus two history textures that swap roles every frame.
+struct NebulaViewTextures {
+    size: UVec2,
+    march: TextureView,
+    history: [TextureView; 2],
+}
+
+#[derive(Resource, Default)]
+struct VolumetricNebulaTextures(HashMap<Entity, NebulaViewTextures>);
+
+fn prepare_textures(
+    render_device: Res<RenderDevice>,
+    mut textures: ResMut<VolumetricNebulaTextures>,
+    view_query: Query<(Entity, &ExtractedView, &VolumetricNebulaSettings)>,
+) {
+    textures.0.retain(|entity, _| view_query.contains(*entity));
+    for (entity, view, settings) in &view_query {
+        let scale = settings.resolution_scale.max(1.0);
+        let size = (view.viewport.zw().as_vec2() / scale)
+            .ceil()
+            .as_uvec2()
+            .max(UVec2::ONE);
+        if textures
+            .0
+            .get(&entity)
+            .is_some_and(|textures| textures.size == size)
+        {
+            continue;
+        }
+        let create_view = |label: &'static str| {
+            render_device
+                .create_texture(&TextureDescriptor {
+                    label: Some(label),
+                    size: Extent3d {
+                        width: size.x,
+                        height: size.y,
+                        depth_or_array_layers: 1,
+                    },
+                    mip_level_count: 1,
+                    sample_count: 1,
+                    dimension: TextureDimension::D2,
+                    format: NEBULA_TEXTURE_FORMAT,
+                    usage: TextureUsages::RENDER_ATTACHMENT | TextureUsages::TEXTURE_BINDING,
+                    view_formats: &[],
+                })
+                .create_view(&default())
+        };
+        textures.0.insert(
+            entity,
+            NebulaViewTextures {
+                size,
+                march: create_view("volumetric_nebula_march_texture"),
+                history: [
+                    create_view("volumetric_nebula_history_texture_a"),
+                    create_view("volumetric_nebula_history_texture_b"),
+                ],
+            },
+        );
+    }
+}
+
+/// The composite pipeline for the view's target format.
+#[derive(Component)]
+struct ViewCompositePipeline(CachedRenderPipelineId);
+
+fn prepare_composite_pipelines(
+    mut commands: Commands,
+    pipeline_cache: Res<PipelineCache>,
+    mut pipelines: ResMut<SpecializedRenderPipelines<VolumetricNebulaPipeline>>,
+    nebula_pipeline: Res<VolumetricNebulaPipeline>,
+    view_query: Query<(Entity, &ViewTarget), With<VolumetricNebulaSettings>>,
+) {
+    for (entity, view_target) in &view_query {
+        let id = pipelines.specialize(
+            &pipeline_cache,
+            &nebula_pipeline,
+            view_target.main_texture_format(),
+        );
+        commands.entity(entity).insert(ViewCompositePipeline(id));
+    }
+}
 
 #[derive(Default)]
 struct VolumetricNebulaNode;
@@ -143,22 +436,30 @@ impl ViewNode for VolumetricNebulaNode {
         &'static VolumetricNebulaSettings,
         &'static DynamicUniformIndex<VolumetricNebulaSettings>,
         &'static ViewPrepassTextures,
+        &'static ViewCompositePipeline,
     );
 
     fn run(
         &self,
-        _graph: &mut RenderGraphContext,
+        graph: &mut RenderGraphContext,
         render_context: &mut RenderContext,
-        (view_target, _post_process_settings, settings_index, _view_prepass_textures): QueryItem<
-            Self::ViewQuery,
-        >,
+        (
+            view_target,
+            post_process_settings,
+            settings_index,
+            view_prepass_textures,
+            composite_pipeline,
+        ): QueryItem<Self::ViewQuery>,
         world: &World,
     ) -> Result<(), NodeRunError> {
         let post_process_pipeline = world.resource::<VolumetricNebulaPipeline>();
         let pipeline_cache = world.resource::<PipelineCache>();
 
-        let Some(pipeline) = pipeline_cache.get_render_pipeline(post_process_pipeline.pipeline_id)
-        else {
+        let (Some(march_pipeline), Some(temporal_pipeline), Some(composite_pipeline)) = (
+            pipeline_cache.get_render_pipeline(post_process_pipeline.march_pipeline_id),
+            pipeline_cache.get_render_pipeline(post_process_pipeline.temporal_pipeline_id),
+            pipeline_cache.get_render_pipeline(composite_pipeline.0),
+        ) else {
             return Ok(());
         };
 
@@ -167,33 +468,84 @@ impl ViewNode for VolumetricNebulaNode {
             return Ok(());
         };
 
-        let post_process = view_target.post_process_write();
-        let bind_group = render_context.render_device().create_bind_group(
-            "post_process_bind_group",
-            &post_process_pipeline.layout,
-            &BindGroupEntries::sequential((
-                post_process.source,
-                &post_process_pipeline.color_sampler,
-                settings_binding.clone(),
+        let Some(depth_view) = view_prepass_textures.depth_view() else {
+            return Ok(());
+        };
+
+        let Some(volumes_binding) = world.resource::<NebulaVolumeBuffer>().0.binding() else {
+            return Ok(());
+        };
+
+        let Some(textures) = world
+            .resource::<VolumetricNebulaTextures>()
+            .0
+            .get(&graph.view_entity())
+        else {
+            return Ok(());
+        };
+        let frame = post_process_settings.frame_index as usize;
+        let history_read = &textures.history[frame % 2];
+        let history_write = &textures.history[(frame + 1) % 2];
+
+        let march_bind_group = render_context.render_device().create_bind_group(
+            "volumetric_nebula_march_bind_group",
+            &post_process_pipeline.march_layout,
+            &BindGroupEntries::with_indices((
+                (2, settings_binding.clone()),
+                (3, depth_view),
+                (8, volumes_binding.clone()),
             )),
         );
+        run_fullscreen_pass(
+            render_context,
+            "volumetric_nebula_march_pass",
+            &textures.march,
+            march_pipeline,
+            &march_bind_group,
+            settings_index.index(),
+        );
 
-        let mut render_pass = render_context.begin_tracked_render_pass(RenderPassDescriptor {
-            label: Some("post_process_pass"),
-            color_attachments: &[Some(RenderPassColorAttachment {
-                view: post_process.destination,
-                resolve_target: None,
-                ops: Operations::default(),
-                depth_slice: None,
-            })],
-            depth_stencil_attachment: None,
-            timestamp_writes: None,
-            occlusion_query_set: None,
-        });
+        let temporal_bind_group = render_context.render_device().create_bind_group(
+            "volumetric_nebula_temporal_bind_group",
+            &post_process_pipeline.temporal_layout,
+            &BindGroupEntries::with_indices((
+                (2, settings_binding.clone()),
+                (3, depth_view),
+                (4, &textures.march),
+                (5, history_read),
+                (6, &post_process_pipeline.nebula_sampler),
+                (8, volumes_binding),
+            )),
+        );
+        run_fullscreen_pass(
+            render_context,
+            "volumetric_nebula_temporal_pass",
+            history_write,
+            temporal_pipeline,
+            &temporal_bind_group,
+            settings_index.index(),
+        );
 
-        render_pass.set_render_pipeline(pipeline);
-        render_pass.set_bind_group(0, &bind_group, &[settings_index.index()]);
-        render_pass.draw(0..3, 0..1);
+        let post_process = view_target.post_process_write();
+        let composite_bind_group = render_context.render_device().create_bind_group(
+            "volumetric_nebula_composite_bind_group",
+            &post_process_pipeline.composite_layout,
+            &BindGroupEntries::with_indices((
+                (0, post_process.source),
+                (1, &post_process_pipeline.color_sampler),
+                (2, settings_binding.clone()),
+                (3, depth_view),
+                (7, history_write),
+            )),
+        );
+        run_fullscreen_pass(
+            render_context,
+            "volumetric_nebula_composite_pass",
+            post_process.destination,
+            composite_pipeline,
+            &composite_bind_group,
+            settings_index.index(),
+        );
 
         Ok(())
     }
@@ -202,56 +554,188 @@ impl ViewNode for VolumetricNebulaNode {
 impl FromWorld for VolumetricNebulaPipeline {
     fn from_world(world: &mut World) -> Self {
         let render_device = world.resource::<RenderDevice>();
-        let layout = render_device.create_bind_group_layout(
-            "post_process_bind_group_layout",
-            &BindGroupLayoutEntries::sequential(
+        let march_layout = render_device.create_bind_group_layout(
+            "volumetric_nebula_march_bind_group_layout",
+            &BindGroupLayoutEntries::with_indices(
+                ShaderStages::FRAGMENT,
+                (
+                    (2, uniform_buffer::<VolumetricNebulaSettings>(true)),
+                    (3, texture_depth_2d_multisampled()),
+                    (8, storage_buffer_read_only::<GpuNebulaVolumes>(false)),
+                ),
+            ),
+        );
+        let temporal_layout = render_device.create_bind_group_layout(
+            "volumetric_nebula_temporal_bind_group_layout",
+            &BindGroupLayoutEntries::with_indices(
+                ShaderStages::FRAGMENT,
+                (
+                    (2, uniform_buffer::<VolumetricNebulaSettings>(true)),
+                    (3, texture_depth_2d_multisampled()),
+                    (4, texture_2d(TextureSampleType::Float { filterable: true })),
+                    (5, texture_2d(TextureSampleType::Float { filterable: true })),
+                    (6, sampler(SamplerBindingType::Filtering)),
+                    (8, storage_buffer_read_only::<GpuNebulaVolumes>(false)),
+                ),
+            ),
+        );
+        let composite_layout = render_device.create_bind_group_layout(
+            "volumetric_nebula_composite_bind_group_layout",
+            &BindGroupLayoutEntries::with_indices(
                 ShaderStages::FRAGMENT,
                 (
-                    texture_2d(TextureSampleType::Float { filterable: true }),
-                    sampler(SamplerBindingType::Filtering),
-                    uniform_buffer::<VolumetricNebulaSettings>(true),
+                    (0, texture_2d(TextureSampleType::Float { filterable: true })),
+                    (1, sampler(SamplerBindingType::Filtering)),
+                    (2, uniform_buffer::<VolumetricNebulaSettings>(true)),
+                    (3, texture_depth_2d_multisampled()),
+                    (7, texture_2d(TextureSampleType::Float { filterable: true })),
                 ),
             ),
         );
 
         let color_sampler = render_device.create_sampler(&SamplerDescriptor::default());
+        let nebula_sampler = render_device.create_sampler(&SamplerDescriptor {
+            mag_filter: FilterMode::Linear,
+            min_filter: FilterMode::Linear,
+            ..default()
+        });
         let shader = world.load_asset("shaders/volumetric_nebula.wgsl");
-        let vertex_state = world.resource::<FullscreenShader>().to_vertex_state();
-        let pipeline_id =
+        let fullscreen_shader = world.resource::<FullscreenShader>().clone();
+        let world: &World = world;
+        let queue_pipeline = |label: &'static str,
+                              layout: &BindGroupLayout,
+                              entry_point: &'static str,
+                              format: TextureFormat| {
+            let descriptor = fullscreen_pipeline_descriptor(
+                &fullscreen_shader,
+                label,
+                layout,
+                shader.clone(),
+                entry_point,
+                format,
+            );
             world
-                .resource_mut::<PipelineCache>()
-                .queue_render_pipeline(RenderPipelineDescriptor {
-                    label: Some("volumetric_nebula_pipeline".into()),
-                    layout: vec![layout.clone()],
-                    vertex: vertex_state,
-                    fragment: Some(FragmentState {
-                        shader,
-                        shader_defs: vec![],
-                        entry_point: Some("fragment".into()),
-                        targets: vec![Some(ColorTargetState {
-                            format: TextureFormat::bevy_default(),
-                            blend: None,
-                            write_mask: ColorWrites::ALL,
-                        })],
-                    }),
-                    primitive: PrimitiveState::default(),
-                    depth_stencil: None,
-                    multisample: MultisampleState::default(),
-                    push_constant_ranges: vec![],
-                    zero_initialize_workgroup_memory: true,
-                });
+                .resource::<PipelineCache>()
+                .queue_render_pipeline(descriptor)
+        };
+        let march_pipeline_id = queue_pipeline(
+            "volumetric_nebula_march_pipeline",
+            &march_layout,
+            "march",
+            NEBULA_TEXTURE_FORMAT,
+        );
+        let temporal_pipeline_id = queue_pipeline(
+            "volumetric_nebula_temporal_pipeline",
+            &temporal_layout,
+            "temporal",
+            NEBULA_TEXTURE_FORMAT,
+        );
 
         Self {
-            layout,
+            march_layout,
+            temporal_layout,
+            composite_layout,
             color_sampler,
-            pipeline_id,
+            nebula_sampler,
+            march_pipeline_id,
+            temporal_pipeline_id,
+            shader,
+            fullscreen_shader,
         }
     }
 }
 
+// Only the composite pass writes to the view target, so it is the one specialized on its format.
+impl SpecializedRenderPipeline for VolumetricNebulaPipeline {
+    type Key = TextureFormat;
+
+    fn specialize(&self, format: TextureFormat) -> RenderPipelineDescriptor {
+        fullscreen_pipeline_descriptor(
+            &self.fullscreen_shader,
+            "volumetric_nebula_composite_pipeline",
+            &self.composite_layout,
+            self.shader.clone(),
+            "composite",
+            format,
+        )
+    }
+}
+
 #[derive(Resource)]
 struct VolumetricNebulaPipeline {
-    layout: BindGroupLayout,
+    march_layout: BindGroupLayout,
+    temporal_layout: BindGroupLayout,
+    composite_layout: BindGroupLayout,
     color_sampler: Sampler,
-    pipeline_id: CachedRenderPipelineId,
+    nebula_sampler: Sampler,
+    march_pipeline_id: CachedRenderPipelineId,
+    temporal_pipeline_id: CachedRenderPipelineId,
+    shader: Handle<Shader>,
+    fullscreen_shader: FullscreenShader,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // `density_at` in `volumetric_nebula.wgsl` at each point, for `volumes` and `settings`,
+    // captured on a software adapter (llvmpipe). Some points are outside the volumes or below
+    // the iso value.
+    const GOLDEN_DENSITIES: [(Vec3, f32); 7] = [
+        (Vec3::new(0.0, 0.0, -120.0), 0.04604146),
+        (Vec3::new(50.0, 20.0, -100.0), 0.24831739),
+        (Vec3::new(-150.0, 30.0, -200.0), 0.059958108),
+        (Vec3::new(0.0, 300.0, 0.0), 0.0),
+        (Vec3::new(380.0, 60.0, 150.0), 0.24725261),
+        (Vec3::new(420.0, 80.0, 120.0), 0.041591693),
+        (Vec3::new(220.0, 40.0, 40.0), 0.0),
+    ];
+
+    fn settings() -> VolumetricNebulaSettings {
+        VolumetricNebulaSettings {
+            time: 3.0,
+            iso_value: 0.5,
+            density: 1.5,
+            ..default()
+        }
+    }
+
+    fn volumes() -> [(GlobalTransform, NebulaVolume); 2] {
+        [
+            (
+                Transform::from_xyz(0.0, 0.0, -120.0).into(),
+                NebulaVolume {
+                    shape: NebulaShape::Sphere { radius: 220.0 },
+                    ..default()
+                },
+            ),
+            (
+                Transform::from_xyz(380.0, 60.0, 150.0)
+                    .with_rotation(Quat::from_rotation_y(0.6))
+                    .into(),
+                NebulaVolume {
+                    shape: NebulaShape::Box {
+                        half_extents: Vec3::new(160.0, 50.0, 90.0),
+                    },
+                    density: 0.8,
+                    noise_scale: 0.06,
+                    animation_speed: 0.2,
+                    ..default()
+                },
+            ),
+        ]
+    }
+
+    #[test]
+    fn density_matches_wgsl_goldens() {
+        let settings = settings();
+        let volumes = volumes();
+        for (point, golden) in GOLDEN_DENSITIES {
+            let density = settings.density_at(point, volumes.iter().map(|(t, v)| (t, v)));
+            assert!(
+                (density - golden).abs() <= 1e-4,
+                "{density} at {point}, expected {golden}"
+            );
+        }
+    }
 }
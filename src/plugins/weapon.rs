@@ -2,10 +2,10 @@ use crate::core::projectile::Projectile;
 use std::sync::Arc;
 
 use avian3d::prelude::*;
-use bevy::prelude::*;
+use bevy::{light::NotShadowCaster, prelude::*};
 
 use crate::{
-    core::player::Player,
+    core::{main_camera::CameraTrauma, player::Player, weapon::WeaponFired},
     resources::weapons::{Weapon, Weapons},
 };
 
@@ -27,9 +27,11 @@ fn update_weapon_slots(
     time: Res<Time>,
     mut materials: ResMut<Assets<StandardMaterial>>,
     mut meshes: ResMut<Assets<Mesh>>,
-    mut query: Query<(&mut WeaponSlots, &Transform)>,
+    mut trauma: MessageWriter<CameraTrauma>,
+    mut fired: MessageWriter<WeaponFired>,
+    mut query: Query<(Entity, &mut WeaponSlots, &Transform, Has<Player>)>,
 ) {
-    for (mut weapon_slots, transform) in query.iter_mut() {
+    for (shooter, mut weapon_slots, transform, is_player) in query.iter_mut() {
         for weapon_slot in weapon_slots.slots.iter_mut() {
             if let Some(weapon_slot) = &mut weapon_slot.1 {
                 match weapon_slot.state {
@@ -42,10 +44,19 @@ fn update_weapon_slots(
                         }
                     }
                     WeaponSlotState::Fired => {
+                        let hardpoint = Transform {
+                            translation: transform.translation + transform.forward() * 2.0,
+                            rotation: transform.rotation,
+                            ..default()
+                        };
                         commands.spawn((
-                            Projectile { lifetime: 1.0 },
+                            Projectile {
+                                lifetime: 1.0,
+                                damage: weapon_slot.weapon.damage(),
+                            },
                             RigidBody::Dynamic,
                             Collider::sphere(0.1),
+                            CollisionEventsEnabled,
                             LinearVelocity(transform.forward() * 200.0),
                             Mesh3d(meshes.add(Capsule3d {
                                 radius: 0.1,
@@ -53,16 +64,20 @@ fn update_weapon_slots(
                             })),
                             MeshMaterial3d(materials.add(StandardMaterial {
                                 base_color: Color::srgb(0.0, 1.0, 0.0),
-                                unlit: true,
+                                emissive: LinearRgba::rgb(1.0, 12.0, 1.0),
                                 ..default()
                             })),
+                            NotShadowCaster,
                             Transform {
-                                translation: transform.translation + transform.forward() * 2.0,
-                                rotation: transform.rotation
+                                rotation: hardpoint.rotation
                                     * Quat::from_rotation_x(std::f32::consts::FRAC_PI_2),
-                                ..default()
+                                ..hardpoint
                             },
                         ));
+                        fired.write(WeaponFired { shooter, hardpoint });
+                        if is_player {
+                            trauma.write(CameraTrauma(weapon_slot.weapon.recoil()));
+                        }
                         weapon_slot.state = WeaponSlotState::Cooldown(weapon_slot.weapon.cooldown())
                     }
                     _ => {}
@@ -75,7 +90,8 @@ fn update_weapon_slots(
 pub struct WeaponPlugin;
 impl Plugin for WeaponPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(Startup, add_components_player)
+        app.add_message::<WeaponFired>()
+            .add_systems(Startup, add_components_player)
             .add_systems(Update, update_weapon_slots)
             .insert_resource(Weapons::default());
     }
@@ -0,0 +1,70 @@
+use bevy::{
+    platform::collections::{HashMap, HashSet},
+    prelude::*,
+};
+
+use crate::{
+    core::asteroid_field::{FieldConfig, FieldLayout},
+    resources::world_seed::{derive_seed, WorldSeed},
+};
+
+/// How the endless asteroid fields are generated and streamed around the player.
+#[derive(Resource)]
+pub struct AsteroidField {
+    /// Together with a field's name and a chunk's coordinate, decides everything in that chunk.
+    pub seed: u64,
+    /// Where in space the asteroids are. Chunks wait for it to load.
+    pub layout: Handle<FieldLayout>,
+    /// Edge length of the cubic chunks.
+    pub chunk_size: f32,
+    /// Chunks whose coordinate is within this many chunks of the player's chunk are loaded.
+    pub load_radius: i32,
+    /// Loaded chunks further out than this are unloaded. Larger than `load_radius`, so flying back
+    /// and forth over a chunk border doesn't reload chunks.
+    pub unload_radius: i32,
+    /// Radius around the origin kept clear for the player to start in.
+    pub clear_radius: f32,
+    /// Caps the work done on a single frame when many chunks come into range at once.
+    pub max_chunk_loads_per_frame: usize,
+}
+
+impl FromWorld for AsteroidField {
+    fn from_world(world: &mut World) -> Self {
+        let layout = world
+            .resource::<AssetServer>()
+            .load("fields/world.fields.ron");
+        Self::new(world.resource::<WorldSeed>(), layout)
+    }
+}
+
+impl AsteroidField {
+    pub fn new(seed: &WorldSeed, layout: Handle<FieldLayout>) -> Self {
+        Self {
+            seed: seed.derive("asteroid_field"),
+            layout,
+            chunk_size: 200.0,
+            load_radius: 2,
+            unload_radius: 3,
+            clear_radius: 15.0,
+            max_chunk_loads_per_frame: 2,
+        }
+    }
+
+    pub fn chunk_coord(&self, position: Vec3) -> IVec3 {
+        (position / self.chunk_size).floor().as_ivec3()
+    }
+
+    /// Seed of `field`, from its name.
+    pub fn field_seed(&self, field: &FieldConfig) -> u64 {
+        derive_seed(self.seed, &field.name)
+    }
+}
+
+#[derive(Resource, Default)]
+pub struct AsteroidChunks {
+    /// Asteroids spawned for each loaded chunk. Some may have been destroyed since.
+    pub loaded: HashMap<IVec3, Vec<Entity>>,
+    /// Field and index of the destroyed asteroids of every chunk visited so far, so they stay gone
+    /// when the chunk is generated again.
+    pub destroyed: HashMap<IVec3, HashSet<(u32, u32)>>,
+}
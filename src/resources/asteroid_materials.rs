@@ -0,0 +1,42 @@
+use bevy::prelude::*;
+
+use crate::core::asteroid::{AsteroidImpostorMaterial, AsteroidMaterial, OreTier, ROCK_COLOR};
+
+/// One material and one impostor material per ore tier, shared by every asteroid of that tier.
+#[derive(Resource)]
+pub struct AsteroidMaterials {
+    materials: [Handle<AsteroidMaterial>; OreTier::ALL.len()],
+    impostors: [Handle<AsteroidImpostorMaterial>; OreTier::ALL.len()],
+}
+
+impl AsteroidMaterials {
+    pub fn get(&self, tier: OreTier) -> &Handle<AsteroidMaterial> {
+        &self.materials[tier as usize]
+    }
+
+    pub fn impostor(&self, tier: OreTier) -> &Handle<AsteroidImpostorMaterial> {
+        &self.impostors[tier as usize]
+    }
+}
+
+impl FromWorld for AsteroidMaterials {
+    fn from_world(world: &mut World) -> Self {
+        let mut materials = world.resource_mut::<Assets<AsteroidMaterial>>();
+        let materials = OreTier::ALL.map(|tier| {
+            materials.add(AsteroidMaterial {
+                base: StandardMaterial {
+                    base_color: ROCK_COLOR,
+                    perceptual_roughness: 0.85,
+                    ..default()
+                },
+                extension: tier.surface(),
+            })
+        });
+        let mut impostors = world.resource_mut::<Assets<AsteroidImpostorMaterial>>();
+        let impostors = OreTier::ALL.map(|tier| impostors.add(tier.impostor()));
+        Self {
+            materials,
+            impostors,
+        }
+    }
+}
@@ -0,0 +1,69 @@
+use avian3d::prelude::Collider;
+use bevy::{platform::collections::HashMap, prelude::*};
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+use crate::{
+    core::asteroid_mesh::{impostor_mesh, AsteroidShape, LOD_SUBDIVISIONS},
+    resources::world_seed::{lattice_seed, WorldSeed},
+};
+
+/// Number of distinct asteroid shapes generated for each chunk. Every chunk gets its own shapes,
+/// so the field never repeats, and random rotation, scale and surface variation keep asteroids
+/// sharing a shape within a chunk from looking alike.
+pub const SHAPES_PER_CHUNK: usize = 8;
+
+pub struct AsteroidVariant {
+    /// Meshes from the closest level of detail to the farthest.
+    pub lods: [Handle<Mesh>; LOD_SUBDIVISIONS.len()],
+    pub collider: Collider,
+}
+
+#[derive(Resource)]
+pub struct AsteroidMeshes {
+    /// Together with a chunk's coordinate, decides the shapes of that chunk.
+    seed: u64,
+    /// Shapes of every loaded chunk.
+    chunks: HashMap<IVec3, Vec<AsteroidVariant>>,
+    /// Billboard shared by every asteroid drawn as an impostor.
+    pub impostor: Handle<Mesh>,
+}
+
+impl FromWorld for AsteroidMeshes {
+    fn from_world(world: &mut World) -> Self {
+        let seed = world.resource::<WorldSeed>().derive("asteroid_shapes");
+        let mut meshes = world.resource_mut::<Assets<Mesh>>();
+        Self {
+            seed,
+            chunks: HashMap::default(),
+            impostor: meshes.add(impostor_mesh()),
+        }
+    }
+}
+
+impl AsteroidMeshes {
+    /// Shapes of the chunk at `coord`, generated the first time the chunk is loaded.
+    pub fn load_chunk(&mut self, meshes: &mut Assets<Mesh>, coord: IVec3) -> &[AsteroidVariant] {
+        let seed = self.seed;
+        self.chunks.entry(coord).or_insert_with(|| {
+            let mut rng = StdRng::seed_from_u64(lattice_seed(seed, coord));
+            (0..SHAPES_PER_CHUNK)
+                .map(|_| {
+                    let shape = AsteroidShape::from_seed(rng.r#gen());
+                    AsteroidVariant {
+                        lods: shape.lod_meshes().map(|mesh| meshes.add(mesh)),
+                        collider: shape.collider(),
+                    }
+                })
+                .collect()
+        })
+    }
+
+    /// Lets go of the chunk's meshes. They are freed once its asteroids are despawned.
+    pub fn unload_chunk(&mut self, coord: IVec3) {
+        self.chunks.remove(&coord);
+    }
+
+    pub fn variant(&self, chunk: IVec3, variant: usize) -> Option<&AsteroidVariant> {
+        self.chunks.get(&chunk)?.get(variant)
+    }
+}
@@ -1,2 +1,7 @@
+pub mod asteroid_field;
+pub mod asteroid_materials;
+pub mod asteroid_meshes;
+pub mod particle_effects;
 pub mod upgrades;
 pub mod weapons;
+pub mod world_seed;
@@ -0,0 +1,25 @@
+use bevy::prelude::*;
+
+use crate::core::particles::ParticleEffect;
+
+#[derive(Resource)]
+pub struct ParticleEffects {
+    pub engine_exhaust: Handle<ParticleEffect>,
+    pub muzzle_flash: Handle<ParticleEffect>,
+    pub impact_sparks: Handle<ParticleEffect>,
+    pub debris: Handle<ParticleEffect>,
+    pub fireball: Handle<ParticleEffect>,
+}
+
+impl FromWorld for ParticleEffects {
+    fn from_world(world: &mut World) -> Self {
+        let asset_server = world.resource::<AssetServer>();
+        Self {
+            engine_exhaust: asset_server.load("particles/engine_exhaust.particle.ron"),
+            muzzle_flash: asset_server.load("particles/muzzle_flash.particle.ron"),
+            impact_sparks: asset_server.load("particles/impact_sparks.particle.ron"),
+            debris: asset_server.load("particles/debris.particle.ron"),
+            fireball: asset_server.load("particles/fireball.particle.ron"),
+        }
+    }
+}
@@ -18,6 +18,10 @@ impl Weapon for TestWeapon {
     fn damage(&self) -> f32 {
         2.0
     }
+
+    fn recoil(&self) -> f32 {
+        0.05
+    }
 }
 
 #[derive(Default)]
@@ -30,9 +34,14 @@ impl Weapon for TestWeapon2 {
     fn damage(&self) -> f32 {
         4.0
     }
+
+    fn recoil(&self) -> f32 {
+        0.3
+    }
 }
 
 pub trait Weapon: Send + Sync {
     fn cooldown(&self) -> f32;
     fn damage(&self) -> f32;
+    fn recoil(&self) -> f32;
 }
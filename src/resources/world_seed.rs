@@ -0,0 +1,169 @@
+use bevy::prelude::*;
+use rand::{rngs::StdRng, SeedableRng};
+
+const SEED_ARGUMENT: &str = "--seed";
+const SEED_VARIABLE: &str = "SPACE_SEED";
+
+/// Seed every procedural system derives its randomness from, so a seed always generates the same
+/// world.
+#[derive(Resource, Clone, Copy, Debug)]
+pub struct WorldSeed(pub u64);
+
+impl WorldSeed {
+    /// Reads the seed from `--seed <seed>` on the command line, or else the `SPACE_SEED`
+    /// environment variable. Seeds that aren't numbers are hashed, so any word works as a seed.
+    /// Without either, the seed is random and logged so the run can be reproduced.
+    pub fn from_env() -> Self {
+        let mut arguments = std::env::args().skip(1);
+        let mut argument = None;
+        while let Some(next) = arguments.next() {
+            if next == SEED_ARGUMENT {
+                argument = arguments.next();
+            } else if let Some(value) = next.strip_prefix("--seed=") {
+                argument = Some(value.to_owned());
+            }
+        }
+        let seed = match argument.or_else(|| std::env::var(SEED_VARIABLE).ok()) {
+            Some(seed) => Self::parse(&seed),
+            None => Self(rand::random()),
+        };
+        info!("world seed: {}", seed.0);
+        seed
+    }
+
+    pub fn parse(seed: &str) -> Self {
+        Self(seed.parse().unwrap_or_else(|_| fnv_1a_64(seed)))
+    }
+
+    /// Seed of the stream called `stream`. Each system draws from its own stream, so changing how
+    /// much randomness one system uses doesn't change what the others generate.
+    pub fn derive(&self, stream: &str) -> u64 {
+        derive_seed(self.0, stream)
+    }
+
+    pub fn rng(&self, stream: &str) -> StdRng {
+        StdRng::seed_from_u64(self.derive(stream))
+    }
+}
+
+/// Seed of the stream called `stream` within the stream seeded by `seed`.
+pub fn derive_seed(seed: u64, stream: &str) -> u64 {
+    splitmix_64(seed ^ fnv_1a_64(stream))
+}
+
+// SplitMix64, a fast hash that spreads nearby inputs over the whole range.
+pub fn splitmix_64(x: u64) -> u64 {
+    let mut z = x.wrapping_add(0x9e3779b97f4a7c15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+    z ^ (z >> 31)
+}
+
+/// Seed of the cell at `coord` of a lattice. Neighbouring cells get unrelated seeds.
+pub fn lattice_seed(seed: u64, coord: IVec3) -> u64 {
+    coord
+        .to_array()
+        .into_iter()
+        .fold(seed, |hash, c| splitmix_64(hash ^ c as u32 as u64))
+}
+
+// FNV-1a. Unlike the standard library's hasher, it gives the same result on every run.
+fn fnv_1a_64(text: &str) -> u64 {
+    text.bytes().fold(0xcbf29ce484222325, |hash, byte| {
+        (hash ^ byte as u64).wrapping_mul(0x100000001b3)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        core::{
+            asteroid_field::FieldConfig, asteroid_mesh::LOD_SUBDIVISIONS, distribution::FieldShape,
+        },
+        resources::{asteroid_field::AsteroidField, asteroid_meshes::AsteroidMeshes},
+    };
+
+    // What the "banana" seed generates in one chunk, so changes to seeding or generation that
+    // would change existing worlds are caught.
+    const GOLDEN_CHUNK: IVec3 = IVec3::new(3, -2, 5);
+    const GOLDEN_ASTEROIDS: [(u32, Vec3, u32); 4] = [
+        (0, Vec3::new(657.32544, -282.42676, 1011.64496), 636184074),
+        (1, Vec3::new(665.2294, -227.42393, 1080.6832), 838224808),
+        (2, Vec3::new(632.1791, -356.1395, 1129.1726), 3420089422),
+        (3, Vec3::new(626.28876, -250.42279, 1008.4676), 3060904794),
+    ];
+    // The first vertices of the coarsest mesh of the chunk's sixth shape.
+    const GOLDEN_VARIANT: usize = 5;
+    const GOLDEN_VERTICES: [[f32; 3]; 4] = [
+        [0.0, 1.0126765, 0.0],
+        [0.7519121, 0.48449174, 0.0],
+        [0.2415319, 0.5036298, 0.6157189],
+        [-0.571285, 0.45500323, 0.34379366],
+    ];
+
+    #[test]
+    fn seeds_parse_the_same_every_time() {
+        assert_eq!(WorldSeed::parse("42").0, 42);
+        assert_eq!(WorldSeed::parse("banana").0, WorldSeed::parse("banana").0);
+        assert_ne!(WorldSeed::parse("banana").0, WorldSeed::parse("apple").0);
+    }
+
+    #[test]
+    fn streams_are_stable_and_independent() {
+        let seed = WorldSeed::parse("banana");
+        assert_eq!(
+            seed.derive("asteroid_field"),
+            WorldSeed::parse("banana").derive("asteroid_field")
+        );
+        assert_ne!(
+            seed.derive("asteroid_field"),
+            seed.derive("asteroid_shapes")
+        );
+        assert_ne!(
+            seed.derive("skybox"),
+            WorldSeed(seed.0 + 1).derive("skybox")
+        );
+    }
+
+    #[test]
+    fn a_seed_generates_the_same_asteroids() {
+        let mut world = World::new();
+        world.insert_resource(WorldSeed::parse("banana"));
+        world.init_resource::<Assets<Mesh>>();
+
+        let field = AsteroidField::new(world.resource::<WorldSeed>(), Handle::default());
+        let drift = FieldConfig {
+            name: "drift".to_owned(),
+            shape: FieldShape::Volume,
+            asteroids_per_chunk: UVec2::new(4, 8),
+        };
+        let asteroids = drift.asteroids(field.field_seed(&drift), field.chunk_size, GOLDEN_CHUNK);
+        assert_eq!(asteroids.len(), GOLDEN_ASTEROIDS.len());
+        for ((index, asteroid), (golden_index, translation, tag)) in
+            asteroids.iter().zip(GOLDEN_ASTEROIDS)
+        {
+            assert_eq!(*index, golden_index);
+            assert!(
+                asteroid.translation.abs_diff_eq(translation, 1e-3),
+                "{} != {translation}",
+                asteroid.translation
+            );
+            assert_eq!(asteroid.tag, tag);
+        }
+
+        let mut asteroid_meshes = AsteroidMeshes::from_world(&mut world);
+        let mut meshes = world.resource_mut::<Assets<Mesh>>();
+        let variant = &asteroid_meshes.load_chunk(&mut meshes, GOLDEN_CHUNK)[GOLDEN_VARIANT];
+        let mesh = meshes
+            .get(&variant.lods[LOD_SUBDIVISIONS.len() - 1])
+            .unwrap();
+        let vertices = mesh.attribute(Mesh::ATTRIBUTE_POSITION).unwrap();
+        for (vertex, golden) in vertices.as_float3().unwrap().iter().zip(GOLDEN_VERTICES) {
+            assert!(
+                Vec3::from(*vertex).abs_diff_eq(golden.into(), 1e-5),
+                "{vertex:?} != {golden:?}"
+            );
+        }
+    }
+}